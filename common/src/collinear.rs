@@ -45,6 +45,17 @@ pub fn condense_collinear_iter(pts: Vec<Point2>) -> Vec<Point2> {
 
 /// Will remove the middle points of groups of 3 collinear points that appear in linear order along the curve.
 pub fn condense_collinear(pts: Vec<Point2>) -> Vec<Point2> {
+    condense_collinear_by(pts, are_collinear)
+}
+
+/// Will remove the middle points of groups of 3 points that `collinear` finds to be on a line.
+///
+/// This lets points that can be compared exactly, like those on a lattice, be condensed without an epsilon.
+pub fn condense_collinear_by<P, F>(pts: Vec<P>, collinear: F) -> Vec<P>
+where
+    P: Copy,
+    F: Fn(P, P, P) -> bool,
+{
     if pts.len() < 3 {
         return pts
     } else {
@@ -52,7 +63,7 @@ pub fn condense_collinear(pts: Vec<Point2>) -> Vec<Point2> {
         let mut candidate = pts[1];
 
         for i in 2..pts.len() {
-            if !are_collinear(ret[ret.len() - 1], candidate, pts[i]) {
+            if !collinear(ret[ret.len() - 1], candidate, pts[i]) {
                 ret.push(candidate)
             }
 
@@ -60,7 +71,7 @@ pub fn condense_collinear(pts: Vec<Point2>) -> Vec<Point2> {
         }
         // Handle the last element, which needs to be added.
         // The current last point may need to be discarded if the previous and the last are collinear with it.
-        if are_collinear(ret[ret.len() - 2], ret[ret.len() - 1], candidate ) {
+        if ret.len() > 1 && collinear(ret[ret.len() - 2], ret[ret.len() - 1], candidate) {
             ret.pop();
         }
         ret.push(candidate);
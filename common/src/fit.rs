@@ -0,0 +1,145 @@
+//! Fitting curves into the window.
+//!
+//! Curves are reified wherever their turtle happens to take them,
+//! so rather than working out an origin and line length for every curve,
+//! they are drawn at any size and then moved and scaled to fill the window.
+//! Nannou puts the origin at the center of the window, so fitted points are centered on it.
+
+use nannou::geom::{Point2, Vector2};
+
+/// The smallest rectangle containing some points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point2,
+    pub max: Point2,
+}
+
+impl BoundingBox {
+    /// The bounding box of some points, or `None` if there aren't any.
+    pub fn of<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item=&'a Point2>,
+    {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(BoundingBox { min: first, max: first }, |bounds, pt| BoundingBox {
+            min: Point2 { x: bounds.min.x.min(pt.x), y: bounds.min.y.min(pt.y) },
+            max: Point2 { x: bounds.max.x.max(pt.x), y: bounds.max.y.max(pt.y) },
+        }))
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Point2 {
+        Point2 {
+            x: (self.min.x + self.max.x) / 2.0,
+            y: (self.min.y + self.max.y) / 2.0,
+        }
+    }
+
+    /// The transform that centers this box in a rectangle of the given dimensions,
+    /// scaling it uniformly to leave a margin on every side of its longer extent.
+    pub fn fit(&self, dimensions: Vector2, margin: f32) -> Fit {
+        let available_x = (dimensions.x - 2.0 * margin).max(0.0);
+        let available_y = (dimensions.y - 2.0 * margin).max(0.0);
+        let scale = match (self.width() > 0.0, self.height() > 0.0) {
+            (true, true) => (available_x / self.width()).min(available_y / self.height()),
+            (true, false) => available_x / self.width(),
+            (false, true) => available_y / self.height(),
+            // A single point has no size to scale.
+            (false, false) => 1.0,
+        };
+        Fit {
+            center: self.center(),
+            scale,
+        }
+    }
+}
+
+/// Moves points so that a center ends up at the origin, and then scales them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fit {
+    pub center: Point2,
+    pub scale: f32,
+}
+
+impl Fit {
+    pub fn apply(&self, pt: Point2) -> Point2 {
+        Point2 {
+            x: (pt.x - self.center.x) * self.scale,
+            y: (pt.y - self.center.y) * self.scale,
+        }
+    }
+
+    /// Where a fitted point came from, such as the curve under the mouse.
+    pub fn invert(&self, pt: Point2) -> Point2 {
+        Point2 {
+            x: pt.x / self.scale + self.center.x,
+            y: pt.y / self.scale + self.center.y,
+        }
+    }
+}
+
+/// Centers points in a rectangle of the given dimensions and scales them to fill it, less a margin on every side.
+pub fn fit(points: Vec<Point2>, dimensions: Vector2, margin: f32) -> Vec<Point2> {
+    match BoundingBox::of(&points) {
+        Some(bounds) => {
+            let fit = bounds.fit(dimensions, margin);
+            points.into_iter().map(|pt| fit.apply(pt)).collect()
+        }
+        None => points,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(a: Point2, b: Point2) {
+        assert!((a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn fills_the_limiting_dimension() {
+        let points = vec![Point2 { x: 10.0, y: 10.0 }, Point2 { x: 14.0, y: 11.0 }, Point2 { x: 12.0, y: 12.0 }];
+        let fitted = fit(points.clone(), Vector2 { x: 100.0, y: 100.0 }, 10.0);
+        // The box is 4 by 2, so the width fills the 80 units left after the margins.
+        assert_close(fitted[0], Point2 { x: -40.0, y: -20.0 });
+        assert_close(fitted[1], Point2 { x: 40.0, y: 0.0 });
+        assert_close(fitted[2], Point2 { x: 0.0, y: 20.0 });
+
+        let bounds = BoundingBox::of(&fitted).unwrap();
+        assert_close(bounds.center(), Point2 { x: 0.0, y: 0.0 });
+
+        let fit = BoundingBox::of(&points).unwrap().fit(Vector2 { x: 100.0, y: 100.0 }, 10.0);
+        assert_close(fit.invert(fitted[1]), points[1]);
+    }
+
+    #[test]
+    fn tall_windows_limit_the_width() {
+        let points = vec![Point2 { x: 0.0, y: 0.0 }, Point2 { x: 1.0, y: 1.0 }];
+        let fitted = fit(points, Vector2 { x: 50.0, y: 200.0 }, 5.0);
+        assert_close(fitted[0], Point2 { x: -20.0, y: -20.0 });
+        assert_close(fitted[1], Point2 { x: 20.0, y: 20.0 });
+    }
+
+    #[test]
+    fn degenerate_boxes_are_centered() {
+        let line = vec![Point2 { x: 3.0, y: 7.0 }, Point2 { x: 3.0, y: 9.0 }];
+        let fitted = fit(line, Vector2 { x: 100.0, y: 40.0 }, 0.0);
+        assert_close(fitted[0], Point2 { x: 0.0, y: -20.0 });
+        assert_close(fitted[1], Point2 { x: 0.0, y: 20.0 });
+
+        let point = fit(vec![Point2 { x: 3.0, y: 7.0 }], Vector2 { x: 100.0, y: 40.0 }, 0.0);
+        assert_close(point[0], Point2 { x: 0.0, y: 0.0 });
+
+        assert!(fit(vec![], Vector2 { x: 100.0, y: 40.0 }, 0.0).is_empty());
+    }
+}
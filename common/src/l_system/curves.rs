@@ -0,0 +1,242 @@
+//! A catalog of classic L-system curves, so they can be generated by name.
+//!
+//! Each entry is a spec for `DynamicGrammar`, along with the iterations that are worth drawing.
+//! The largest of these draws at most a few million lines.
+
+use super::dynamic::DynamicGrammar;
+use std::ops::RangeInclusive;
+
+/// A named curve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    pub name: &'static str,
+    /// The axiom, angle and productions, in the format read by `DynamicGrammar::parse`.
+    pub spec: &'static str,
+    /// The iterations that are recommended for drawing.
+    pub iterations: RangeInclusive<usize>,
+}
+
+impl Curve {
+    pub fn grammar(&self) -> DynamicGrammar {
+        DynamicGrammar::parse(self.spec).expect("Catalog specs are valid.")
+    }
+}
+
+/// Every curve in the catalog.
+pub const CURVES: &[Curve] = &[
+    Curve {
+        name: "koch-snowflake",
+        spec: "
+            axiom = F--F--F
+            angle = 60
+            F -> F+F--F+F
+        ",
+        iterations: 0..=7,
+    },
+    Curve {
+        name: "gosper",
+        spec: "
+            axiom = A
+            angle = 60
+            draw = AB
+            A -> A-B--B+A++AA+B-
+            B -> +A-BB--B-A++A+B
+        ",
+        iterations: 0..=7,
+    },
+    Curve {
+        name: "sierpinski-triangle",
+        spec: "
+            axiom = F-G-G
+            angle = 120
+            draw = FG
+            F -> F-G+F+G-F
+            G -> GG
+        ",
+        iterations: 0..=10,
+    },
+    Curve {
+        name: "peano",
+        spec: "
+            axiom = L
+            angle = 90
+            L -> LFRFL-F-RFLFR+F+LFRFL
+            R -> RFLFR+F+LFRFL-F-RFLFR
+        ",
+        iterations: 0..=6,
+    },
+    Curve {
+        name: "dragon",
+        spec: "
+            axiom = FX
+            angle = 90
+            X -> X+YF+
+            Y -> -FX-Y
+        ",
+        iterations: 0..=20,
+    },
+    Curve {
+        name: "twin-dragon",
+        spec: "
+            axiom = FX+FX+
+            angle = 90
+            X -> X+YF+
+            Y -> -FX-Y
+        ",
+        iterations: 0..=19,
+    },
+    Curve {
+        name: "terdragon",
+        spec: "
+            axiom = F
+            angle = 120
+            F -> F+F-F
+        ",
+        iterations: 0..=12,
+    },
+    Curve {
+        name: "levy-c",
+        spec: "
+            axiom = F
+            angle = 45
+            F -> +F--F+
+        ",
+        iterations: 0..=20,
+    },
+    Curve {
+        name: "moore",
+        spec: "
+            axiom = LFL+F+LFL
+            angle = 90
+            L -> -RF+LFL+FR-
+            R -> +LF-RFR-FL+
+        ",
+        iterations: 0..=8,
+    },
+    Curve {
+        name: "sierpinski-arrowhead",
+        spec: "
+            axiom = A
+            angle = 60
+            draw = AB
+            A -> B-A-B
+            B -> A+B+A
+        ",
+        iterations: 0..=12,
+    },
+    Curve {
+        name: "quadratic-koch-island",
+        spec: "
+            axiom = F+F+F+F
+            angle = 90
+            F -> F+F-F-FF+F+F-F
+        ",
+        iterations: 0..=5,
+    },
+    Curve {
+        name: "cesaro",
+        spec: "
+            axiom = F
+            angle = 85
+            F -> F+F--F+F
+        ",
+        iterations: 0..=9,
+    },
+    Curve {
+        name: "hilbert",
+        spec: "
+            axiom = A
+            angle = 90
+            A -> +BF-AFA-FB+
+            B -> -AF+BFB+FA-
+        ",
+        iterations: 0..=9,
+    },
+];
+
+/// Finds a curve in the catalog.
+pub fn by_name(name: &str) -> Option<&'static Curve> {
+    CURVES.iter().find(|curve| curve.name == name)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::l_system::lattice::LatticePoint;
+    use std::collections::HashSet;
+
+    #[test]
+    fn names_are_unique() {
+        let names: HashSet<&str> = CURVES.iter().map(|curve| curve.name).collect();
+        assert_eq!(names.len(), CURVES.len());
+        assert_eq!(by_name("hilbert").map(|curve| curve.name), Some("hilbert"));
+        assert_eq!(by_name("hilbert 2"), None);
+    }
+
+    #[test]
+    fn iterations_stay_within_budget() {
+        CURVES.iter().for_each(|curve| {
+            let growth = curve.grammar().into_lsystem().growth().unwrap();
+            let lines = growth.drawing_len(*curve.iterations.end());
+            assert!(lines > 0 && lines <= 1 << 22, "{} draws {} lines", curve.name, lines);
+        });
+    }
+
+    #[test]
+    fn hilbert_visits_every_cell() {
+        let n = 4;
+        let lsystem = by_name("hilbert").unwrap().grammar().into_lsystem().iterate_n(n);
+        let (_, points) = lsystem.reify_lattice_iter(std::f32::consts::FRAC_PI_2).unwrap();
+        let cells: HashSet<LatticePoint> = points.collect();
+        let side = 1 << n;
+        assert_eq!(cells.len(), side * side);
+    }
+
+    #[test]
+    fn closed_curves_close() {
+        ["koch-snowflake", "sierpinski-triangle", "quadratic-koch-island"]
+            .iter()
+            .for_each(|name| {
+                let lsystem = by_name(name).unwrap().grammar().into_lsystem().iterate_n(3);
+                let (_, points) = lsystem.reify_lattice_iter(by_name(name).unwrap().grammar().angle()).unwrap();
+                let points: Vec<LatticePoint> = points.collect();
+                assert_eq!(points[0], points[points.len() - 1], "{}", name);
+            });
+    }
+
+    #[test]
+    fn moore_ends_next_to_its_start() {
+        let lsystem = by_name("moore").unwrap().grammar().into_lsystem().iterate_n(3);
+        let (_, points) = lsystem.reify_lattice_iter(std::f32::consts::FRAC_PI_2).unwrap();
+        let points: Vec<LatticePoint> = points.collect();
+        let (first, last) = (points[0], points[points.len() - 1]);
+        assert_eq!((last.a - first.a).abs() + (last.b - first.b).abs(), 1);
+    }
+
+    #[test]
+    fn catalog_curves_are_well_formed() {
+        CURVES.iter().for_each(|curve| {
+            let grammar = curve.grammar();
+            let angle = grammar.angle();
+            let lsystem = grammar.into_lsystem();
+            let alphabet = lsystem.iter().next().unwrap().alphabet();
+            let diagnostics = lsystem.diagnose(&alphabet, angle, 4).unwrap();
+            assert!(diagnostics.unreachable.is_empty(), "{}", curve.name);
+            assert!(diagnostics.barren.is_empty(), "{}", curve.name);
+            assert!(diagnostics.growth_rate > 1.0, "{}", curve.name);
+
+            let closes = ["koch-snowflake", "sierpinski-triangle", "quadratic-koch-island"].contains(&curve.name);
+            assert_eq!(diagnostics.closes, closes, "{}", curve.name);
+            // The dragons touch themselves at their corners, and the Lévy curve and triangle retrace their lines.
+            let meets_itself = [
+                "sierpinski-triangle",
+                "dragon",
+                "twin-dragon",
+                "terdragon",
+                "levy-c",
+            ].contains(&curve.name);
+            assert_eq!(diagnostics.intersection.is_some(), meets_itself, "{} {:?}", curve.name, diagnostics.intersection);
+        });
+    }
+}
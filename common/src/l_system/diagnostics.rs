@@ -0,0 +1,239 @@
+//! Checking a grammar for mistakes that would otherwise only show up when it's drawn.
+//!
+//! Some properties come from the grammar alone: which symbols can ever appear,
+//! which keep growing without drawing anything, and how quickly iterations grow.
+//! Others depend on the angle, so they are found by drawing an iteration:
+//! whether the curve ends where it started, and whether it runs into itself.
+//! A wrong angle usually shows up as a curve that should close but doesn't, or one that suddenly overlaps itself.
+
+use super::growth::{draws, reachable};
+use super::{Grammar, LSystem};
+use nannou::geom::Point2;
+use std::collections::HashMap;
+
+/// How close two points need to be to count as the same, for lines of unit length.
+const EPSILON: f32 = 0.001;
+
+/// What was found out about a grammar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostics<T> {
+    /// Symbols of the alphabet that never appear in any iteration.
+    pub unreachable: Vec<T>,
+    /// Symbols that keep being rewritten without ever leading to a line.
+    pub barren: Vec<T>,
+    /// The factor by which the number of symbols grows with each iteration in the long run.
+    pub growth_rate: f64,
+    /// Whether the last line of the iteration ends where the first one starts.
+    pub closes: bool,
+    /// The first pair of lines found to meet anywhere other than where one follows on from the other,
+    /// as indices into the lines of the iteration in the order they're drawn.
+    ///
+    /// Lines that only touch at a corner count, as do lines that retrace each other.
+    /// Branches meet where they fork, so this is only meaningful for grammars that draw a single path.
+    pub intersection: Option<(usize, usize)>,
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar<Item=Point2> + Clone + PartialEq,
+{
+    /// Checks the grammar, drawing its nth iteration at the given angle to see how the curve behaves.
+    ///
+    /// The alphabet is every symbol that the grammar is meant to use.
+    /// Grammars with more than `MAX_SYMBOLS` distinct symbols can't be checked, and give `None`.
+    pub fn diagnose(&self, alphabet: &[T], angle: f32, n: usize) -> Option<Diagnostics<T>> {
+        let (symbols, _, productions) = reachable(&self.working_set)?;
+        let growth = self.growth()?;
+
+        let unreachable = alphabet
+            .iter()
+            .filter(|v| !symbols.contains(v))
+            .cloned()
+            .collect();
+
+        let barren = barren(&symbols, &productions)
+            .into_iter()
+            .map(|i| symbols[i].clone())
+            .collect();
+
+        let iteration = self.clone().iterate_n(n);
+        let segments: Vec<(Point2, Point2)> = iteration
+            .reify_segments_iter(angle, 1.0, Point2 { x: 0.0, y: 0.0 })
+            .collect();
+        let closes = match (segments.first(), segments.last()) {
+            (Some((start, _)), Some((_, end))) => distance(*start, *end) < EPSILON,
+            _ => false,
+        };
+
+        Some(Diagnostics {
+            unreachable,
+            barren,
+            growth_rate: growth.growth_rate(),
+            closes,
+            intersection: first_intersection(&segments, closes),
+        })
+    }
+}
+
+/// Finds the symbols that are rewritten into something other than themselves forever,
+/// without any of their descendants drawing a line.
+fn barren<T>(symbols: &[T], productions: &[Vec<usize>]) -> Vec<usize>
+where
+    T: Grammar<Item=Point2>,
+{
+    let len = symbols.len();
+    // Symbols that only produce themselves are left alone, so they aren't followed.
+    let rewrites = |i: usize| productions[i].len() != 1 || productions[i][0] != i;
+
+    // Whether each symbol leads to a line, found by spreading backwards from the symbols that draw.
+    let mut can_draw: Vec<bool> = symbols.iter().map(draws).collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..len {
+            if !can_draw[i] && productions[i].iter().any(|j| can_draw[*j]) {
+                can_draw[i] = true;
+                changed = true;
+            }
+        }
+    }
+
+    (0..len)
+        .filter(|i| !can_draw[*i] && rewrites(*i))
+        .filter(|i| {
+            // Whether the symbol can grow back into itself.
+            let mut seen = vec![false; len];
+            let mut stack = productions[*i].clone();
+            while let Some(j) = stack.pop() {
+                if j == *i {
+                    return true
+                }
+                if !seen[j] {
+                    seen[j] = true;
+                    stack.extend(productions[j].iter().cloned());
+                }
+            }
+            false
+        })
+        .collect()
+}
+
+/// Finds the first pair of lines that meet anywhere other than the corner where one follows the other.
+///
+/// Lines are sorted into a grid of unit cells, so only lines that are near each other are compared.
+fn first_intersection(segments: &[(Point2, Point2)], closed: bool) -> Option<(usize, usize)> {
+    let cell_range = |a: f32, b: f32| {
+        let min = (a.min(b) - EPSILON).floor() as i64;
+        let max = (a.max(b) + EPSILON).floor() as i64;
+        min..=max
+    };
+    let mut cells: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+
+    for (j, (start, end)) in segments.iter().enumerate() {
+        let mut nearby: Vec<usize> = vec![];
+        for x in cell_range(start.x, end.x) {
+            for y in cell_range(start.y, end.y) {
+                let cell = cells.entry((x, y)).or_default();
+                nearby.extend(cell.iter().cloned());
+                cell.push(j);
+            }
+        }
+        nearby.sort();
+        nearby.dedup();
+        let found = nearby
+            .into_iter()
+            .find(|i| {
+                let last = segments.len() - 1;
+                if *i + 1 == j {
+                    overlaps(segments[*i], segments[j])
+                } else if closed && *i == 0 && j == last {
+                    overlaps(segments[j], segments[0])
+                } else {
+                    segment_distance(segments[*i], segments[j]) < EPSILON
+                }
+            });
+        if let Some(i) = found {
+            return Some((i, j))
+        }
+    }
+    None
+}
+
+/// Whether a line that follows on from another doubles back over it.
+fn overlaps(first: (Point2, Point2), second: (Point2, Point2)) -> bool {
+    point_segment_distance(second.1, first) < EPSILON || point_segment_distance(first.0, second) < EPSILON
+}
+
+fn distance(a: Point2, b: Point2) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+fn point_segment_distance(pt: Point2, (start, end): (Point2, Point2)) -> f32 {
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    let length_squared = dx * dx + dy * dy;
+    if length_squared == 0.0 {
+        return distance(pt, start)
+    }
+    let t = ((pt.x - start.x) * dx + (pt.y - start.y) * dy) / length_squared;
+    if t <= 0.0 {
+        distance(pt, start)
+    } else if t >= 1.0 {
+        distance(pt, end)
+    } else {
+        distance(pt, Point2 { x: start.x + t * dx, y: start.y + t * dy })
+    }
+}
+
+/// Twice the signed area of the triangle, positive when the points turn counterclockwise.
+fn orientation(a: Point2, b: Point2, c: Point2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn segment_distance(a: (Point2, Point2), b: (Point2, Point2)) -> f32 {
+    let crosses = orientation(a.0, a.1, b.0) * orientation(a.0, a.1, b.1) < 0.0
+        && orientation(b.0, b.1, a.0) * orientation(b.0, b.1, a.1) < 0.0;
+    if crosses {
+        return 0.0
+    }
+    point_segment_distance(a.0, b)
+        .min(point_segment_distance(a.1, b))
+        .min(point_segment_distance(b.0, a))
+        .min(point_segment_distance(b.1, a))
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::l_system::dynamic::DynamicGrammar;
+
+    #[test]
+    fn unreachable_and_barren_symbols_are_found() {
+        let lsystem = DynamicGrammar::parse("axiom = FA\nF -> F+F\nA -> A+\nZ -> ZF")
+            .unwrap()
+            .into_lsystem();
+        let alphabet = lsystem.iter().next().unwrap().alphabet();
+        let diagnostics = lsystem.diagnose(&alphabet, std::f32::consts::FRAC_PI_2, 2).unwrap();
+        let unreachable: Vec<char> = diagnostics.unreachable.iter().map(|v| v.symbol()).collect();
+        let barren: Vec<char> = diagnostics.barren.iter().map(|v| v.symbol()).collect();
+        assert_eq!(unreachable, vec!['Z']);
+        assert_eq!(barren, vec!['A']);
+        assert!((diagnostics.growth_rate - 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn crossings_are_found() {
+        // A square with a tail that runs back across its first side.
+        let lsystem = DynamicGrammar::parse("axiom = FF+F+F+FF").unwrap().into_lsystem();
+        let alphabet = lsystem.iter().next().unwrap().alphabet();
+        let diagnostics = lsystem.diagnose(&alphabet, std::f32::consts::FRAC_PI_2, 0).unwrap();
+        assert!(!diagnostics.closes);
+        assert_eq!(diagnostics.intersection, Some((0, 4)));
+
+        // Turning around retraces the last line.
+        let lsystem = DynamicGrammar::parse("axiom = F|F").unwrap().into_lsystem();
+        let alphabet = lsystem.iter().next().unwrap().alphabet();
+        let diagnostics = lsystem.diagnose(&alphabet, std::f32::consts::FRAC_PI_2, 0).unwrap();
+        assert_eq!(diagnostics.intersection, Some((0, 1)));
+    }
+}
@@ -0,0 +1,452 @@
+//! Grammars defined at runtime from a textual spec.
+//!
+//! A spec consists of settings and productions, one per line:
+//!
+//! ```text
+//! # Koch snowflake
+//! axiom = F--F--F
+//! angle = 60
+//! F -> F+F--F+F
+//! ```
+//!
+//! The angle is given in degrees.
+//! By default, `F` draws a line, `f` moves without drawing, `+` and `-` turn left and right,
+//! `|` turns around, and `[` and `]` push and pop the turtle's state.
+//! When drawn in three dimensions, `&` and `^` pitch down and up, and `\` and `/` roll left and right.
+//! These can be replaced by the `draw`, `move`, `left`, `right`, `around`, `push`, `pop`,
+//! `pitch_down`, `pitch_up`, `roll_left` and `roll_right` settings,
+//! each of which takes a list of characters.
+//! Any other character is kept in the working set but does nothing when reified.
+
+use super::{Alphabet, Grammar, LSystem};
+use nannou::geom::Point2;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub use super::turtle::Command;
+
+/// A grammar parsed from a textual spec.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamicGrammar {
+    axiom: Vec<char>,
+    /// Angle in radians.
+    angle: f32,
+    productions: HashMap<char, Vec<char>>,
+    commands: HashMap<char, Command>,
+}
+
+impl DynamicGrammar {
+    pub fn parse(spec: &str) -> Result<Self, ParseError> {
+        let mut axiom = None;
+        let mut angle = std::f32::consts::FRAC_PI_2;
+        let mut productions = HashMap::new();
+        let mut commands: HashMap<char, Command> = [
+            ('F', Command::Draw),
+            ('f', Command::Move),
+            ('+', Command::TurnLeft),
+            ('-', Command::TurnRight),
+            ('|', Command::TurnAround),
+            ('[', Command::Push),
+            (']', Command::Pop),
+            ('&', Command::PitchDown),
+            ('^', Command::PitchUp),
+            ('\\', Command::RollLeft),
+            ('/', Command::RollRight),
+        ].iter().cloned().collect();
+
+        let mut line_count = 0;
+        for (index, line) in spec.lines().enumerate() {
+            line_count = index + 1;
+            let error = |column: usize, kind: ParseErrorKind| ParseError {
+                line: index + 1,
+                column,
+                kind
+            };
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let start_column = column_of(line, line.len() - line.trim_start().len());
+
+            if let Some(arrow) = line.find("->") {
+                let predecessor = line[..arrow].trim();
+                let mut chars = predecessor.chars();
+                let symbol = match (chars.next(), chars.next()) {
+                    (Some(symbol), None) => symbol,
+                    _ => return Err(error(start_column, ParseErrorKind::InvalidPredecessor))
+                };
+                if productions.contains_key(&symbol) {
+                    return Err(error(start_column, ParseErrorKind::DuplicateProduction(symbol)));
+                }
+                let successor = line[arrow + 2..].chars().filter(|c| !c.is_whitespace()).collect();
+                productions.insert(symbol, successor);
+            } else if let Some(equals) = line.find('=') {
+                let key = line[..equals].trim();
+                let value = line[equals + 1..].trim();
+                let value_column = column_of(line, line.len() - line[equals + 1..].trim_start().len());
+                let value_chars: Vec<char> = value.chars().filter(|c| !c.is_whitespace()).collect();
+                let command = match key {
+                    "axiom" => {
+                        if axiom.is_some() {
+                            return Err(error(start_column, ParseErrorKind::DuplicateAxiom));
+                        }
+                        axiom = Some(value_chars);
+                        continue;
+                    }
+                    "angle" => {
+                        angle = value.parse::<f32>()
+                            .map_err(|_| error(value_column, ParseErrorKind::InvalidAngle))?
+                            .to_radians();
+                        continue;
+                    }
+                    "draw" => Command::Draw,
+                    "move" => Command::Move,
+                    "left" => Command::TurnLeft,
+                    "right" => Command::TurnRight,
+                    "around" => Command::TurnAround,
+                    "push" => Command::Push,
+                    "pop" => Command::Pop,
+                    "pitch_down" => Command::PitchDown,
+                    "pitch_up" => Command::PitchUp,
+                    "roll_left" => Command::RollLeft,
+                    "roll_right" => Command::RollRight,
+                    _ => return Err(error(start_column, ParseErrorKind::UnknownSetting(key.to_string())))
+                };
+                // The setting replaces the default characters for the command.
+                commands.retain(|_, c| *c != command);
+                value_chars.into_iter().for_each(|c| {
+                    commands.insert(c, command);
+                });
+            } else {
+                return Err(error(start_column, ParseErrorKind::ExpectedProductionOrSetting));
+            }
+        }
+
+        let axiom = axiom.ok_or(ParseError {
+            line: line_count + 1,
+            column: 1,
+            kind: ParseErrorKind::MissingAxiom
+        })?;
+
+        Ok(DynamicGrammar {
+            axiom,
+            angle,
+            productions,
+            commands
+        })
+    }
+
+    /// Creates a grammar from its parts.
+    ///
+    /// Symbols without a production are left unchanged when iterating.
+    pub fn new(axiom: Vec<char>, angle: f32, productions: HashMap<char, Vec<char>>, commands: HashMap<char, Command>) -> Self {
+        DynamicGrammar {
+            axiom,
+            angle,
+            productions,
+            commands
+        }
+    }
+
+    /// Converts a built-in alphabet to a dynamic grammar.
+    ///
+    /// The commands are taken from the Fractint conventions that the alphabet's characters follow.
+    pub fn from_alphabet<T: Alphabet>(axiom: &[T], angle: f32) -> Self {
+        let productions = T::alphabet()
+            .into_iter()
+            .filter_map(|v| {
+                let symbol = v.to_char();
+                let successor: Vec<char> = v.production_rules()
+                    .iter()
+                    .map(Alphabet::to_char)
+                    .collect();
+                // Symbols that only produce themselves don't need a production.
+                if successor == [symbol] {
+                    None
+                } else {
+                    Some((symbol, successor))
+                }
+            })
+            .collect();
+        let commands = T::alphabet()
+            .iter()
+            .map(Alphabet::to_char)
+            .filter_map(|symbol| fractint_command(symbol).map(|command| (symbol, command)))
+            .collect();
+
+        DynamicGrammar {
+            axiom: axiom.iter().map(Alphabet::to_char).collect(),
+            angle,
+            productions,
+            commands
+        }
+    }
+
+    /// The angle to turn by, in radians.
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    pub fn axiom(&self) -> &[char] {
+        &self.axiom
+    }
+
+    /// The symbol's production, if it has one.
+    pub fn production(&self, symbol: char) -> Option<&[char]> {
+        self.productions.get(&symbol).map(Vec::as_slice)
+    }
+
+    /// Every production, ordered by the symbol being replaced.
+    pub fn productions(&self) -> Vec<(char, &[char])> {
+        let mut productions: Vec<(char, &[char])> = self.productions
+            .iter()
+            .map(|(symbol, successor)| (*symbol, successor.as_slice()))
+            .collect();
+        productions.sort_by_key(|(symbol, _)| *symbol);
+        productions
+    }
+
+    /// What the character does when reified, if anything.
+    pub fn command(&self, symbol: char) -> Option<Command> {
+        self.commands.get(&symbol).cloned()
+    }
+
+    /// Creates an L-system starting from the grammar's axiom.
+    pub fn into_lsystem(self) -> LSystem<DynamicSymbol> {
+        let grammar = Arc::new(self);
+        let axiom = grammar.axiom
+            .iter()
+            .map(|symbol| DynamicSymbol {
+                symbol: *symbol,
+                grammar: grammar.clone()
+            })
+            .collect();
+        LSystem::new(axiom)
+    }
+}
+
+impl FromStr for DynamicGrammar {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DynamicGrammar::parse(s)
+    }
+}
+
+/// The command that Fractint associates with a character, if any.
+pub fn fractint_command(symbol: char) -> Option<Command> {
+    match symbol.to_ascii_uppercase() {
+        'F' | 'D' => Some(Command::Draw),
+        'G' | 'M' => Some(Command::Move),
+        '+' => Some(Command::TurnLeft),
+        '-' => Some(Command::TurnRight),
+        '|' => Some(Command::TurnAround),
+        '[' => Some(Command::Push),
+        ']' => Some(Command::Pop),
+        _ => None
+    }
+}
+
+/// Converts a byte offset within a line to a 1-based column.
+fn column_of(line: &str, byte_offset: usize) -> usize {
+    line[..byte_offset].chars().count() + 1
+}
+
+/// A symbol of a dynamic grammar.
+///
+/// Each symbol shares the grammar it came from, so that it can be expanded and reified like any other alphabet.
+#[derive(Clone, Debug)]
+pub struct DynamicSymbol {
+    symbol: char,
+    grammar: Arc<DynamicGrammar>,
+}
+
+impl DynamicSymbol {
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+
+    /// The grammar that the symbol came from.
+    pub fn grammar(&self) -> &DynamicGrammar {
+        &self.grammar
+    }
+
+    /// Every symbol that appears in the grammar's axiom or productions, in order.
+    pub fn alphabet(&self) -> Vec<DynamicSymbol> {
+        let mut symbols: Vec<char> = self.grammar.axiom
+            .iter()
+            .chain(self.grammar.productions.keys())
+            .chain(self.grammar.productions.values().flatten())
+            .cloned()
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+            .into_iter()
+            .map(|symbol| DynamicSymbol {
+                symbol,
+                grammar: self.grammar.clone()
+            })
+            .collect()
+    }
+}
+
+impl PartialEq for DynamicSymbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+    }
+}
+
+impl Grammar for DynamicSymbol {
+    type Item = Point2;
+
+    fn production_rules(self) -> Vec<Self> {
+        match self.grammar.productions.get(&self.symbol) {
+            Some(successor) => successor
+                .iter()
+                .map(|symbol| DynamicSymbol {
+                    symbol: *symbol,
+                    grammar: self.grammar.clone()
+                })
+                .collect(),
+            None => vec![self]
+        }
+    }
+
+    fn command(&self) -> Option<Command> {
+        self.grammar.command(self.symbol)
+    }
+
+    fn ignored_in_context(&self) -> bool {
+        use Command::*;
+        let command = self.grammar.command(self.symbol);
+        [TurnLeft, TurnRight, TurnAround, PitchDown, PitchUp, RollLeft, RollRight]
+            .iter()
+            .any(|turn| command == Some(*turn))
+    }
+}
+
+/// An error encountered while parsing a spec, located by its 1-based line and column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// The line is neither a `key = value` setting nor an `X -> ...` production.
+    ExpectedProductionOrSetting,
+    /// The left side of a production isn't a single character.
+    InvalidPredecessor,
+    DuplicateProduction(char),
+    DuplicateAxiom,
+    MissingAxiom,
+    /// The angle isn't a number, or for Fractint files, isn't a positive whole number of divisions.
+    InvalidAngle,
+    UnknownSetting(String),
+    /// Expected the start of a named entry, like `Name {`.
+    ExpectedEntry,
+    /// The named entry was never closed with a `}`.
+    UnclosedEntry(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::ExpectedProductionOrSetting => write!(f, "expected a production (`X -> ...`) or a setting (`key = value`)"),
+            ParseErrorKind::InvalidPredecessor => write!(f, "the left side of a production must be a single character"),
+            ParseErrorKind::DuplicateProduction(symbol) => write!(f, "`{}` already has a production", symbol),
+            ParseErrorKind::DuplicateAxiom => write!(f, "the axiom is already set"),
+            ParseErrorKind::MissingAxiom => write!(f, "no axiom was given"),
+            ParseErrorKind::InvalidAngle => write!(f, "the angle must be a number of degrees"),
+            ParseErrorKind::UnknownSetting(key) => write!(f, "unknown setting `{}`", key),
+            ParseErrorKind::ExpectedEntry => write!(f, "expected the start of an entry (`Name {{`)"),
+            ParseErrorKind::UnclosedEntry(name) => write!(f, "`{}` is missing its closing `}}`", name),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KOCH: &str = "
+        # Koch snowflake
+        axiom = F--F--F
+        angle = 60
+        F -> F+F--F+F
+    ";
+
+    fn symbols(lsystem: &LSystem<DynamicSymbol>) -> String {
+        lsystem.iter().map(DynamicSymbol::symbol).collect()
+    }
+
+    #[test]
+    fn koch_expands() {
+        let lsystem = DynamicGrammar::parse(KOCH).unwrap().into_lsystem();
+        assert_eq!(symbols(&lsystem), "F--F--F");
+        let lsystem = lsystem.iterate();
+        assert_eq!(symbols(&lsystem), "F+F--F+F--F+F--F+F--F+F--F+F");
+    }
+
+    #[test]
+    fn koch_reifies() {
+        let grammar: DynamicGrammar = KOCH.parse().unwrap();
+        let angle = grammar.angle();
+        let lsystem = grammar.into_lsystem();
+        let points: Vec<Point2> = lsystem.reify_iter(angle, 1.0, Point2::default()).collect();
+        assert_eq!(points.len(), 4);
+        assert!((points[2].x - 0.5).abs() < 0.0001);
+        assert!((points[2].y + 0.75f32.sqrt()).abs() < 0.0001);
+        // The triangle is closed.
+        assert!(points[3].x.abs() < 0.0001 && points[3].y.abs() < 0.0001);
+    }
+
+    #[test]
+    fn commands_can_be_remapped() {
+        let grammar = DynamicGrammar::parse("axiom = A\ndraw = AB\nleft = <\n").unwrap();
+        assert_eq!(grammar.command('A'), Some(Command::Draw));
+        assert_eq!(grammar.command('B'), Some(Command::Draw));
+        assert_eq!(grammar.command('F'), None);
+        assert_eq!(grammar.command('<'), Some(Command::TurnLeft));
+        assert_eq!(grammar.command('+'), None);
+        assert_eq!(grammar.command('-'), Some(Command::TurnRight));
+    }
+
+    #[test]
+    fn move_breaks_branches() {
+        let lsystem = DynamicGrammar::parse("axiom = FfF").unwrap().into_lsystem();
+        let branches: Vec<Vec<Point2>> = lsystem.reify_branches_iter(0.0, 1.0, Point2::default()).collect();
+        assert_eq!(branches, vec![
+            vec![Point2 { x: 0.0, y: 0.0 }, Point2 { x: 1.0, y: 0.0 }],
+            vec![Point2 { x: 2.0, y: 0.0 }, Point2 { x: 3.0, y: 0.0 }],
+        ]);
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let error = DynamicGrammar::parse("axiom = F\n  FF -> F").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (2, 3, ParseErrorKind::InvalidPredecessor));
+
+        let error = DynamicGrammar::parse("axiom = F\nangle =  sixty").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (2, 10, ParseErrorKind::InvalidAngle));
+
+        let error = DynamicGrammar::parse("axiom = F\nF -> FF\n F -> F").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (3, 2, ParseErrorKind::DuplicateProduction('F')));
+
+        let error = DynamicGrammar::parse("axiom = F\nF F F").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (2, 1, ParseErrorKind::ExpectedProductionOrSetting));
+
+        let error = DynamicGrammar::parse("F -> FF\n").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind.clone()), (2, 1, ParseErrorKind::MissingAxiom));
+        assert_eq!(error.to_string(), "2:1: no axiom was given");
+    }
+}
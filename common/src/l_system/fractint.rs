@@ -0,0 +1,264 @@
+//! Reading and writing Fractint's `.l` files.
+//!
+//! A file holds any number of named entries:
+//!
+//! ```text
+//! Koch { ; Comments start with a semicolon
+//!   Angle 6
+//!   Axiom F--F--F
+//!   F=F+F--F+F
+//! }
+//! ```
+//!
+//! `Angle` is the number of steps that a full turn is divided into.
+//! Symbols aren't case sensitive, so they are read as upper case.
+//! Fractint commands that have no equivalent here, like `!` or `@`,
+//! are kept in the working set but do nothing when reified.
+
+use super::dynamic::{fractint_command, DynamicGrammar, ParseError, ParseErrorKind};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Reads every entry of a `.l` file into a named grammar.
+pub fn parse(text: &str) -> Result<Vec<(String, DynamicGrammar)>, ParseError> {
+    let mut grammars = vec![];
+    let mut entry: Option<Entry> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let error = |offset: usize, kind: ParseErrorKind| ParseError {
+            line: index + 1,
+            column: line[..offset].chars().count() + 1,
+            kind
+        };
+        let line = line.split(';').next().unwrap_or_default();
+        let start = line.len() - line.trim_start().len();
+        let content = line.trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        match entry.as_mut() {
+            None => {
+                let brace = content.find('{').ok_or_else(|| error(start, ParseErrorKind::ExpectedEntry))?;
+                let name = content[..brace].trim();
+                if name.is_empty() || !content[brace + 1..].trim().is_empty() {
+                    return Err(error(start, ParseErrorKind::ExpectedEntry));
+                }
+                entry = Some(Entry {
+                    name: name.to_string(),
+                    line: index + 1,
+                    axiom: None,
+                    angle: None,
+                    productions: HashMap::new()
+                });
+            }
+            Some(_) if content == "}" => {
+                let finished = entry.take().expect("The entry is open.");
+                let name = finished.name.clone();
+                grammars.push((name, finished.into_grammar(index + 1, start + 1)?));
+            }
+            Some(open) => {
+                let mut words = content.splitn(2, char::is_whitespace);
+                let keyword = words.next().unwrap_or_default();
+                let value = words.next().unwrap_or_default().trim();
+                let value_start = line.len() - line[start + keyword.len()..].trim_start().len();
+
+                if keyword.eq_ignore_ascii_case("angle") {
+                    let divisions: u32 = value.parse()
+                        .ok()
+                        .filter(|divisions| *divisions > 0)
+                        .ok_or_else(|| error(value_start, ParseErrorKind::InvalidAngle))?;
+                    open.angle = Some(std::f32::consts::PI * 2.0 / divisions as f32);
+                } else if keyword.eq_ignore_ascii_case("axiom") {
+                    if open.axiom.is_some() {
+                        return Err(error(start, ParseErrorKind::DuplicateAxiom));
+                    }
+                    open.axiom = Some(symbols(value));
+                } else if let Some(equals) = content.find('=') {
+                    let mut predecessor = symbols(&content[..equals]).into_iter();
+                    let symbol = match (predecessor.next(), predecessor.next()) {
+                        (Some(symbol), None) => symbol,
+                        _ => return Err(error(start, ParseErrorKind::InvalidPredecessor))
+                    };
+                    if open.productions.contains_key(&symbol) {
+                        return Err(error(start, ParseErrorKind::DuplicateProduction(symbol)));
+                    }
+                    open.productions.insert(symbol, symbols(&content[equals + 1..]));
+                } else {
+                    return Err(error(start, ParseErrorKind::ExpectedProductionOrSetting));
+                }
+            }
+        }
+    }
+
+    match entry {
+        Some(open) => Err(ParseError {
+            line: open.line,
+            column: 1,
+            kind: ParseErrorKind::UnclosedEntry(open.name)
+        }),
+        None => Ok(grammars)
+    }
+}
+
+/// Writes a grammar as a named `.l` entry.
+pub fn write(name: &str, grammar: &DynamicGrammar) -> Result<String, ExportError> {
+    let divisions = std::f32::consts::PI * 2.0 / grammar.angle();
+    if !divisions.is_finite() || divisions.round() < 1.0 || (divisions - divisions.round()).abs() > 0.0001 {
+        return Err(ExportError::UnrepresentableAngle(grammar.angle()))
+    }
+
+    let productions = grammar.productions();
+    let symbols = grammar.axiom()
+        .iter()
+        .chain(productions.iter().flat_map(|(symbol, successor)| std::iter::once(symbol).chain(successor.iter())));
+    for symbol in symbols {
+        let representable = !symbol.is_ascii_lowercase()
+            && !symbol.is_whitespace()
+            && !"{};=".contains(*symbol)
+            && grammar.command(*symbol) == fractint_command(*symbol);
+        if !representable {
+            return Err(ExportError::UnrepresentableSymbol(*symbol))
+        }
+    }
+
+    let mut text = format!("{} {{\n", name);
+    text += &format!("  Angle {}\n", divisions.round() as u32);
+    text += &format!("  Axiom {}\n", grammar.axiom().iter().collect::<String>());
+    productions.iter().for_each(|(symbol, successor)| {
+        text += &format!("  {}={}\n", symbol, successor.iter().collect::<String>());
+    });
+    text += "}\n";
+    Ok(text)
+}
+
+/// Reasons that a grammar can't be written to a `.l` file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExportError {
+    /// The angle doesn't divide a full turn into a whole number of steps.
+    UnrepresentableAngle(f32),
+    /// Fractint would interpret the symbol differently than the grammar does.
+    UnrepresentableSymbol(char),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::UnrepresentableAngle(angle) => write!(f, "{} radians doesn't divide a full turn evenly", angle),
+            ExportError::UnrepresentableSymbol(symbol) => write!(f, "`{}` can't be represented in Fractint's format", symbol),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// An entry that is still being read.
+struct Entry {
+    name: String,
+    /// The line the entry started on.
+    line: usize,
+    axiom: Option<Vec<char>>,
+    angle: Option<f32>,
+    productions: HashMap<char, Vec<char>>,
+}
+
+impl Entry {
+    fn into_grammar(self, line: usize, column: usize) -> Result<DynamicGrammar, ParseError> {
+        let axiom = self.axiom.ok_or(ParseError {
+            line,
+            column,
+            kind: ParseErrorKind::MissingAxiom
+        })?;
+        let commands = axiom
+            .iter()
+            .chain(self.productions.keys())
+            .chain(self.productions.values().flatten())
+            .filter_map(|symbol| fractint_command(*symbol).map(|command| (*symbol, command)))
+            .collect();
+        let angle = self.angle.unwrap_or(std::f32::consts::FRAC_PI_2);
+        Ok(DynamicGrammar::new(axiom, angle, self.productions, commands))
+    }
+}
+
+/// The upper case symbols of a string, ignoring whitespace.
+fn symbols(s: &str) -> Vec<char> {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::l_system::dynamic::DynamicSymbol;
+    use crate::l_system::LSystem;
+
+    const FILE: &str = "
+; A couple of curves
+Koch1 { ; The snowflake
+  Angle 6
+  Axiom F--F--F
+  F=F+F--F+F
+}
+
+Dragon {
+  angle 4
+  axiom fx
+  x=x+yf+
+  y=-fx-y
+}
+";
+
+    fn symbols(lsystem: &LSystem<DynamicSymbol>) -> String {
+        lsystem.iter().map(DynamicSymbol::symbol).collect()
+    }
+
+    #[test]
+    fn reads_entries() {
+        let grammars = parse(FILE).unwrap();
+        let names: Vec<&str> = grammars.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["Koch1", "Dragon"]);
+
+        let (_, dragon) = &grammars[1];
+        assert!((dragon.angle() - std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+        let lsystem = dragon.clone().into_lsystem().iterate();
+        assert_eq!(symbols(&lsystem), "FX+YF+");
+    }
+
+    #[test]
+    fn round_trips() {
+        let grammars = parse(FILE).unwrap();
+        let text: String = grammars
+            .iter()
+            .map(|(name, grammar)| write(name, grammar).unwrap())
+            .collect();
+        assert_eq!(text, "Koch1 {\n  Angle 6\n  Axiom F--F--F\n  F=F+F--F+F\n}\nDragon {\n  Angle 4\n  Axiom FX\n  X=X+YF+\n  Y=-FX-Y\n}\n");
+        assert_eq!(parse(&text).unwrap(), grammars);
+    }
+
+    #[test]
+    fn rejects_unrepresentable_grammars() {
+        let grammar = DynamicGrammar::parse("axiom = F\nangle = 50").unwrap();
+        assert_eq!(write("Bad", &grammar), Err(ExportError::UnrepresentableAngle(grammar.angle())));
+
+        let grammar = DynamicGrammar::parse("axiom = A\ndraw = A").unwrap();
+        assert_eq!(write("Bad", &grammar), Err(ExportError::UnrepresentableSymbol('A')));
+    }
+
+    #[test]
+    fn errors_are_located() {
+        let error = parse("Koch {\n  Angle six\n}").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (2, 9, ParseErrorKind::InvalidAngle));
+
+        let error = parse("\nKoch {\n  Angle 6\n").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (2, 1, ParseErrorKind::UnclosedEntry("Koch".to_string())));
+
+        let error = parse("Koch {\n  Angle 6\n }").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (3, 2, ParseErrorKind::MissingAxiom));
+
+        let error = parse("  F=F").unwrap_err();
+        assert_eq!((error.line, error.column, error.kind), (1, 3, ParseErrorKind::ExpectedEntry));
+    }
+}
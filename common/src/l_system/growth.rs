@@ -0,0 +1,262 @@
+//! Predicting the size of an iteration without expanding it.
+//!
+//! Every symbol that can be reached from the axiom gets a row in the growth matrix,
+//! counting how many of each symbol its production contains.
+//! The number of each symbol after n iterations is then the axiom's counts multiplied by the matrix raised to the nth power.
+
+use super::turtle::Turtle2;
+use super::{Grammar, LSystem};
+
+/// The most distinct symbols that will be listed before giving up.
+///
+/// Parametric grammars whose parameters can take endless values, like a counter that goes up every iteration,
+/// would otherwise keep finding new symbols forever.
+pub const MAX_SYMBOLS: usize = 1 << 10;
+
+/// The growth matrix of an L-system, along with the symbol counts of its current working set.
+#[derive(Clone, Debug)]
+pub struct Growth<T> {
+    /// Every symbol that can be reached from the working set.
+    symbols: Vec<T>,
+    /// `matrix[i][j]` is the number of `symbols[j]` in the production of `symbols[i]`.
+    matrix: Vec<Vec<u64>>,
+    /// The number of each symbol in the working set.
+    initial: Vec<u64>,
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar + Clone + PartialEq,
+{
+    /// Builds the growth matrix for the symbols that can be reached from the working set,
+    /// or `None` if there are more than `MAX_SYMBOLS` of them.
+    pub fn growth(&self) -> Option<Growth<T>> {
+        let (symbols, working_set, productions) = reachable(&self.working_set)?;
+        let len = symbols.len();
+
+        let mut initial = vec![0; len];
+        working_set.into_iter().for_each(|i| initial[i] += 1);
+
+        let matrix = productions
+            .into_iter()
+            .map(|production| {
+                let mut row = vec![0; len];
+                production.into_iter().for_each(|j| row[j] += 1);
+                row
+            })
+            .collect();
+
+        Some(Growth {
+            symbols,
+            matrix,
+            initial,
+        })
+    }
+}
+
+/// Every symbol that can be reached, followed by the working set and the production of each symbol as indices into them.
+pub(super) type Reachable<T> = (Vec<T>, Vec<usize>, Vec<Vec<usize>>);
+
+/// Finds every symbol that can be reached from the working set, or `None` if there are more than `MAX_SYMBOLS`.
+pub(super) fn reachable<T>(working_set: &[T]) -> Option<Reachable<T>>
+where
+    T: Grammar + Clone + PartialEq,
+{
+    let mut symbols: Vec<T> = vec![];
+    let index_of = |symbols: &mut Vec<T>, v: T| -> usize {
+        match symbols.iter().position(|s| *s == v) {
+            Some(i) => i,
+            None => {
+                symbols.push(v);
+                symbols.len() - 1
+            }
+        }
+    };
+
+    let working_set = working_set
+        .iter()
+        .map(|v| index_of(&mut symbols, v.clone()))
+        .collect();
+
+    // Symbols found while expanding are added to the end, so this visits every reachable symbol.
+    let mut productions = vec![];
+    let mut i = 0;
+    while i < symbols.len() {
+        if symbols.len() > MAX_SYMBOLS {
+            return None
+        }
+        let production = symbols[i]
+            .clone()
+            .production_rules()
+            .into_iter()
+            .map(|v| index_of(&mut symbols, v))
+            .collect();
+        productions.push(production);
+        i += 1;
+    }
+
+    if symbols.len() > MAX_SYMBOLS {
+        return None
+    }
+    Some((symbols, working_set, productions))
+}
+
+impl <T> Growth<T>
+where
+    T: Grammar,
+{
+    /// Every symbol that can appear in some iteration.
+    pub fn symbols(&self) -> &[T] {
+        &self.symbols
+    }
+
+    /// The number of each symbol after n iterations, in the same order as `symbols()`.
+    ///
+    /// Counts that would overflow saturate at `u64::MAX`.
+    pub fn counts(&self, n: usize) -> Vec<u64> {
+        let mut counts = self.initial.clone();
+        let mut power = self.matrix.clone();
+        let mut n = n;
+        while n > 0 {
+            if n & 1 == 1 {
+                counts = multiply_vector(&counts, &power);
+            }
+            n >>= 1;
+            if n > 0 {
+                power = multiply(&power, &power);
+            }
+        }
+        counts
+    }
+
+    /// The number of symbols after n iterations.
+    pub fn len(&self, n: usize) -> u64 {
+        self.counts(n)
+            .into_iter()
+            .fold(0, u64::saturating_add)
+    }
+
+    /// The number of symbols that draw a point after n iterations.
+    pub fn drawing_len(&self, n: usize) -> u64
+    where
+        T::Item: Default
+    {
+        self.counts(n)
+            .into_iter()
+            .zip(self.symbols.iter())
+            .filter(|(_, v)| draws(*v))
+            .fold(0, |acc, (count, _)| acc.saturating_add(count))
+    }
+
+    /// The factor by which the number of symbols grows with each iteration in the long run.
+    ///
+    /// This is the spectral radius of the growth matrix,
+    /// estimated from the size of its entries when raised to a very large power.
+    pub fn growth_rate(&self) -> f64 {
+        const SQUARINGS: i32 = 24;
+        let mut power: Vec<Vec<f64>> = self.matrix
+            .iter()
+            .map(|row| row.iter().map(|x| *x as f64).collect())
+            .collect();
+        let mut log_scale = 0.0;
+        for squaring in 0..=SQUARINGS {
+            if squaring > 0 {
+                power = multiply_f64(&power, &power);
+                log_scale *= 2.0;
+            }
+            // Keep the entries from overflowing by tracking their scale separately.
+            let max = power.iter().flatten().cloned().fold(0.0, f64::max);
+            if max == 0.0 {
+                return 0.0
+            }
+            power.iter_mut().flatten().for_each(|x| *x /= max);
+            log_scale += max.ln();
+        }
+        (log_scale / 2.0f64.powi(SQUARINGS)).exp()
+    }
+}
+
+/// Whether reifying the symbol produces a point.
+pub(super) fn draws<T: Grammar>(v: &T) -> bool
+where
+    T::Item: Default
+{
+    let mut turtle = Turtle2::new(T::Item::default(), 1.0, 1.0);
+    v.stack_op().is_none() && T::reify(v, &mut turtle).is_some()
+}
+
+fn multiply_vector(v: &[u64], m: &[Vec<u64>]) -> Vec<u64> {
+    (0..v.len())
+        .map(|j| {
+            (0..v.len()).fold(0, |acc: u64, i| acc.saturating_add(v[i].saturating_mul(m[i][j])))
+        })
+        .collect()
+}
+
+fn multiply(a: &[Vec<u64>], b: &[Vec<u64>]) -> Vec<Vec<u64>> {
+    a.iter()
+        .map(|row| multiply_vector(row, b))
+        .collect()
+}
+
+fn multiply_f64(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    a.iter()
+        .map(|row| {
+            (0..row.len())
+                .map(|j| (0..row.len()).map(|k| row[k] * b[k][j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::l_system::dynamic::DynamicGrammar;
+
+    const DRAGON: &str = "
+        axiom = FX
+        X -> X+YF+
+        Y -> -FX-Y
+    ";
+
+    #[test]
+    fn counts_match_expansion() {
+        let lsystem = DynamicGrammar::parse(DRAGON).unwrap().into_lsystem();
+        let growth = lsystem.growth().unwrap();
+        (0..8).for_each(|n| {
+            let expanded = lsystem.clone().iterate_n(n);
+            assert_eq!(growth.len(n), expanded.len() as u64);
+            let drawing = expanded.iter().filter(|v| v.symbol() == 'F').count();
+            assert_eq!(growth.drawing_len(n), drawing as u64);
+        });
+    }
+
+    #[test]
+    fn capacity_is_predicted() {
+        let lsystem = DynamicGrammar::parse(DRAGON).unwrap().into_lsystem();
+        let sized = lsystem.clone().iterate_n_with_capacity(8);
+        let plain = lsystem.clone().iterate_n(8);
+        assert_eq!(sized.iter().collect::<Vec<_>>(), plain.iter().collect::<Vec<_>>());
+        assert_eq!(sized.len() as u64, lsystem.growth().unwrap().len(8));
+    }
+
+    #[test]
+    fn deep_iterations_are_predicted() {
+        let growth = DynamicGrammar::parse(DRAGON).unwrap().into_lsystem().growth().unwrap();
+        assert_eq!(growth.drawing_len(30), 1 << 30);
+        assert_eq!(growth.len(200), u64::MAX);
+    }
+
+    #[test]
+    fn growth_rate() {
+        let growth = DynamicGrammar::parse(DRAGON).unwrap().into_lsystem().growth().unwrap();
+        assert!((growth.growth_rate() - 2.0).abs() < 0.001);
+
+        let growth = DynamicGrammar::parse("axiom = F\nF -> F+F-F").unwrap().into_lsystem().growth().unwrap();
+        assert!((growth.growth_rate() - 3.0).abs() < 0.001);
+
+        let growth = DynamicGrammar::parse("axiom = F\nF -> F").unwrap().into_lsystem().growth().unwrap();
+        assert!((growth.growth_rate() - 1.0).abs() < 0.001);
+    }
+}
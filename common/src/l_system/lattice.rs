@@ -0,0 +1,300 @@
+//! Drawing L-systems on an exact integer lattice.
+//!
+//! When a grammar's angle is a multiple of 90° or 60°, every line it draws is one of a handful of unit vectors,
+//! so every point is an integer combination of two of them.
+//! Points on the square lattice are Gaussian integers, `a + bi`,
+//! and points on the triangular lattice are Eisenstein integers, `a + bω` with ω a sixth of a turn.
+//! Turning only changes which unit vector is next, so nothing drifts no matter how long the curve is,
+//! and points can be compared for equality.
+//! They are only turned into floating point coordinates at the very end.
+
+use super::turtle::{Command, Turtle};
+use super::{Grammar, LSystem};
+use nannou::geom::Point2;
+use std::ops::{Add, Neg};
+
+/// The lattices that a turtle can be kept on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lattice {
+    /// The square lattice, for multiples of 90°.
+    Gaussian,
+    /// The triangular lattice, for multiples of 60°.
+    Eisenstein,
+}
+
+/// A point on a lattice, `a` steps along the x axis and `b` steps along the lattice's other unit vector.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LatticePoint {
+    pub a: i64,
+    pub b: i64,
+}
+
+impl Add for LatticePoint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        LatticePoint {
+            a: self.a + other.a,
+            b: self.b + other.b,
+        }
+    }
+}
+
+impl Neg for LatticePoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        LatticePoint {
+            a: -self.a,
+            b: -self.b,
+        }
+    }
+}
+
+impl LatticePoint {
+    /// Whether three points lie on a line.
+    ///
+    /// Both lattices are linear maps of the plane, so this is the usual test, without any rounding.
+    pub fn are_collinear(pt1: LatticePoint, pt2: LatticePoint, pt3: LatticePoint) -> bool {
+        (pt2.a - pt1.a) * (pt3.b - pt1.b) == (pt2.b - pt1.b) * (pt3.a - pt1.a)
+    }
+}
+
+impl Lattice {
+    /// Finds the lattice that an angle keeps the turtle on, along with the number of unit vectors it turns by.
+    ///
+    /// The square lattice is preferred for angles that fit both.
+    pub fn for_angle(angle: f32) -> Option<(Lattice, i64)> {
+        [Lattice::Gaussian, Lattice::Eisenstein]
+            .iter()
+            .filter_map(|lattice| {
+                let steps = angle / (2.0 * std::f32::consts::PI) * lattice.directions() as f32;
+                if (steps - steps.round()).abs() < 0.0001 {
+                    Some((*lattice, steps.round() as i64))
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
+    /// The number of unit vectors, which make one full turn.
+    pub fn directions(self) -> i64 {
+        match self {
+            Lattice::Gaussian => 4,
+            Lattice::Eisenstein => 6,
+        }
+    }
+
+    /// The unit vector a number of steps counterclockwise from the x axis.
+    pub fn unit(self, heading: i64) -> LatticePoint {
+        let half_turn = self.directions() / 2;
+        let heading = heading.rem_euclid(self.directions());
+        // The first half of the turn, where the rest are their negations.
+        let unit = match (self, heading % half_turn) {
+            (_, 0) => LatticePoint { a: 1, b: 0 },
+            (Lattice::Gaussian, _) | (Lattice::Eisenstein, 1) => LatticePoint { a: 0, b: 1 },
+            // ω² = ω - 1
+            _ => LatticePoint { a: -1, b: 1 },
+        };
+        if heading < half_turn {
+            unit
+        } else {
+            -unit
+        }
+    }
+
+    /// Where a lattice point lies in the plane, with lines of the given length starting from the origin.
+    pub fn to_point(self, pt: LatticePoint, line_length: f32, origin: Point2) -> Point2 {
+        let a = pt.a as f64;
+        let b = pt.b as f64;
+        let (x, y) = match self {
+            Lattice::Gaussian => (a, b),
+            Lattice::Eisenstein => (a + b / 2.0, b * 3.0f64.sqrt() / 2.0),
+        };
+        Point2 {
+            x: origin.x + (x * line_length as f64) as f32,
+            y: origin.y + (y * line_length as f64) as f32,
+        }
+    }
+}
+
+/// A turtle that only moves between lattice points.
+///
+/// Lines can't be scaled and stay on the lattice, so `ScaleLength` is ignored,
+/// as are the commands that leave the plane.
+#[derive(Clone, Debug)]
+pub struct LatticeTurtle {
+    lattice: Lattice,
+    position: LatticePoint,
+    /// The number of unit vectors counterclockwise from the x axis.
+    heading: i64,
+    /// The number of unit vectors turned by `TurnLeft` and `TurnRight`.
+    turn: i64,
+    stack: Vec<(LatticePoint, i64)>,
+}
+
+impl LatticeTurtle {
+    /// A turtle at the lattice's origin, facing along the x axis.
+    pub fn new(lattice: Lattice, turn: i64) -> Self {
+        LatticeTurtle {
+            lattice,
+            position: LatticePoint::default(),
+            heading: 0,
+            turn,
+            stack: vec![],
+        }
+    }
+
+    pub fn position(&self) -> LatticePoint {
+        self.position
+    }
+}
+
+impl Turtle for LatticeTurtle {
+    type Point = LatticePoint;
+
+    fn apply(&mut self, command: Command) -> Option<LatticePoint> {
+        let start = self.position;
+        match command {
+            Command::Draw => {
+                self.position = self.position + self.lattice.unit(self.heading);
+                return Some(start)
+            }
+            Command::Move => self.position = self.position + self.lattice.unit(self.heading),
+            Command::TurnLeft => self.heading += self.turn,
+            Command::TurnRight => self.heading -= self.turn,
+            Command::TurnAround => self.heading += self.lattice.directions() / 2,
+            Command::Push => self.stack.push((self.position, self.heading)),
+            Command::Pop => {
+                if let Some((position, heading)) = self.stack.pop() {
+                    self.position = position;
+                    self.heading = heading;
+                }
+            }
+            Command::ScaleLength(_)
+            | Command::PitchDown
+            | Command::PitchUp
+            | Command::RollLeft
+            | Command::RollRight => {}
+        }
+        self.heading = self.heading.rem_euclid(self.lattice.directions());
+        None
+    }
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar,
+{
+    /// Turns the working set into a series of lattice points, using the command of each symbol.
+    ///
+    /// Like `reify_iter`, these are the start of every line, followed by the end of the last one.
+    /// Returns `None` when the angle isn't a multiple of 90° or 60°.
+    pub fn reify_lattice_iter(&self, angle: f32) -> Option<(Lattice, impl Iterator<Item=LatticePoint> + '_)> {
+        let (lattice, turn) = Lattice::for_angle(angle)?;
+        let mut turtle = LatticeTurtle::new(lattice, turn);
+        let mut end = None;
+        let points = self.iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .filter_map(move |v| match v {
+                Some(v) => {
+                    let start = turtle.apply(v.command()?)?;
+                    end = Some(turtle.position());
+                    Some(start)
+                }
+                None => end.take(),
+            });
+        Some((lattice, points))
+    }
+
+    /// Turns the working set into a series of points that are computed exactly on a lattice,
+    /// and only then placed in the plane.
+    ///
+    /// Returns `None` when the angle isn't a multiple of 90° or 60°.
+    pub fn reify_exact_iter(&self, angle: f32, line_length: f32, origin: Point2) -> Option<impl Iterator<Item=Point2> + '_> {
+        let (lattice, points) = self.reify_lattice_iter(angle)?;
+        Some(points.map(move |pt| lattice.to_point(pt, line_length, origin)))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::l_system::dynamic::DynamicGrammar;
+
+    #[test]
+    fn angles_pick_a_lattice() {
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_3, PI};
+        assert_eq!(Lattice::for_angle(FRAC_PI_2), Some((Lattice::Gaussian, 1)));
+        assert_eq!(Lattice::for_angle(PI), Some((Lattice::Gaussian, 2)));
+        assert_eq!(Lattice::for_angle(FRAC_PI_3), Some((Lattice::Eisenstein, 1)));
+        assert_eq!(Lattice::for_angle(2.0 * FRAC_PI_3), Some((Lattice::Eisenstein, 2)));
+        assert_eq!(Lattice::for_angle(PI / 4.0), None);
+        assert_eq!(Lattice::for_angle(25.7f32.to_radians()), None);
+    }
+
+    #[test]
+    fn units_make_a_full_turn() {
+        [Lattice::Gaussian, Lattice::Eisenstein].iter().for_each(|lattice| {
+            let origin = Point2 { x: 0.0, y: 0.0 };
+            let n = lattice.directions();
+            (0..n).for_each(|k| {
+                let pt = lattice.to_point(lattice.unit(k), 1.0, origin);
+                let angle = 2.0 * std::f32::consts::PI * k as f32 / n as f32;
+                assert!((pt.x - angle.cos()).abs() < 0.0001 && (pt.y - angle.sin()).abs() < 0.0001, "{:?} {}", lattice, k);
+            });
+            assert_eq!(lattice.unit(-1), lattice.unit(n - 1));
+        });
+    }
+
+    #[test]
+    fn dragon_ends_exactly() {
+        // (1 + i)^20 = (2i)^10 = -2^10
+        let lsystem = DynamicGrammar::parse("axiom = FX\nX -> X+YF+\nY -> -FX-Y\nangle = 90")
+            .unwrap()
+            .into_lsystem()
+            .iterate_n(20);
+        let (lattice, points) = lsystem.reify_lattice_iter(std::f32::consts::FRAC_PI_2).unwrap();
+        assert_eq!(lattice, Lattice::Gaussian);
+        assert_eq!(points.last(), Some(LatticePoint { a: -(1 << 10), b: 0 }));
+    }
+
+    #[test]
+    fn terdragon_ends_exactly() {
+        // The first iteration ends at 1 + ω, and (1 + ω)^6 = (3ω)^3 = -27.
+        let lsystem = DynamicGrammar::parse("axiom = F\nF -> F+F-F\nangle = 120")
+            .unwrap()
+            .into_lsystem()
+            .iterate_n(6);
+        let (lattice, points) = lsystem.reify_lattice_iter(2.0 * std::f32::consts::FRAC_PI_3).unwrap();
+        assert_eq!(lattice, Lattice::Eisenstein);
+        assert_eq!(points.last(), Some(LatticePoint { a: -27, b: 0 }));
+    }
+
+    #[test]
+    fn exact_points_match_reification() {
+        let lsystem = DynamicGrammar::parse("axiom = F--F--F\nF -> F+F--F+F\nangle = 60")
+            .unwrap()
+            .into_lsystem()
+            .iterate_n(4);
+        let origin = Point2 { x: 3.0, y: -2.0 };
+        let expected: Vec<Point2> = lsystem.reify_iter(std::f32::consts::FRAC_PI_3, 2.0, origin).collect();
+        let points: Vec<Point2> = lsystem.reify_exact_iter(std::f32::consts::FRAC_PI_3, 2.0, origin).unwrap().collect();
+        assert_eq!(points.len(), expected.len());
+        points.iter().zip(expected.iter()).for_each(|(a, b)| {
+            assert!((a.x - b.x).abs() < 0.01 && (a.y - b.y).abs() < 0.01, "{:?} != {:?}", a, b);
+        });
+        // The snowflake closes exactly.
+        assert_eq!(points[0], points[points.len() - 1]);
+    }
+
+    #[test]
+    fn collinearity_is_exact() {
+        let pt = |a, b| LatticePoint { a, b };
+        assert!(LatticePoint::are_collinear(pt(0, 0), pt(1 << 30, 1 << 29), pt(1 << 31, 1 << 30)));
+        assert!(!LatticePoint::are_collinear(pt(0, 0), pt(1 << 30, 1 << 29), pt(1 << 31, (1 << 30) + 1)));
+    }
+}
@@ -0,0 +1,238 @@
+//! A memoized expansion of an L-system, for reifying deep iterations quickly.
+//!
+//! Every occurrence of a symbol expanded to the same depth draws the same shape,
+//! only moved to where the turtle is and turned to the way it faces.
+//! So each (symbol, depth) pair becomes a single node of a DAG, shared by every place that it appears.
+//! Nodes cache the turtle's net movement over their expansion,
+//! and small ones cache their points as well, so reifying copies points instead of expanding symbols.
+
+use super::growth::reachable;
+use super::random_access::{leaf_transforms, tabulate, Transform};
+use super::{Grammar, LSystem};
+use nannou::geom::Point2;
+
+/// The most points that a node will cache.
+const CACHED_POINTS: u64 = 1 << 10;
+
+/// An iteration of an L-system, stored as a DAG of shared sub-expansions.
+#[derive(Clone, Debug)]
+pub struct Memo {
+    working_set: Vec<usize>,
+    productions: Vec<Vec<usize>>,
+    /// `nodes[d][i]` is the ith symbol expanded d times.
+    nodes: Vec<Vec<Node>>,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    transform: Transform,
+    /// The points of the expansion, as drawn by a turtle starting at the origin and facing along the x axis.
+    points: Option<Vec<Point2>>,
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar<Item=Point2> + Clone + PartialEq,
+{
+    /// Builds the DAG of the nth iteration.
+    ///
+    /// Only one node is built for each symbol at each depth,
+    /// so this takes time and memory proportional to n rather than to the length of the iteration.
+    /// Grammars with more than `MAX_SYMBOLS` distinct symbols give `None`.
+    ///
+    /// Nodes only know the turtle's net movement, not what it saved along the way,
+    /// so branching grammars can't be memoized either, and give `None` if any symbol operates on the stack.
+    pub fn memoize(&self, n: usize, angle: f32, line_length: f32) -> Option<Memo> {
+        let (symbols, working_set, productions) = reachable(&self.working_set)?;
+        if symbols.iter().any(|v| v.stack_op().is_some()) {
+            return None
+        }
+        let leaves = leaf_transforms(&symbols, angle, line_length);
+        let transforms = tabulate(n, &productions, leaves, Transform::default(), |acc, t| acc.then(t));
+
+        let mut nodes: Vec<Vec<Node>> = vec![];
+        for (depth, row) in transforms.into_iter().enumerate() {
+            let row = row
+                .into_iter()
+                .enumerate()
+                .map(|(i, transform)| {
+                    let points = if transform.points > CACHED_POINTS {
+                        None
+                    } else if depth == 0 {
+                        Some(vec![Point2 { x: 0.0, y: 0.0 }; transform.points as usize])
+                    } else {
+                        // The children are no bigger than their parent, so their points are cached too.
+                        let mut points = Vec::with_capacity(transform.points as usize);
+                        let mut turtle = Transform::default();
+                        let origin = Point2 { x: 0.0, y: 0.0 };
+                        productions[i].iter().for_each(|j| {
+                            let child = &nodes[depth - 1][*j];
+                            let cached = child.points.as_ref().expect("Smaller nodes are cached.");
+                            points.extend(cached.iter().map(|pt| turtle.place(*pt, origin)));
+                            turtle = turtle.then(&child.transform);
+                        });
+                        Some(points)
+                    };
+                    Node { transform, points }
+                })
+                .collect();
+            nodes.push(row);
+        }
+
+        Some(Memo {
+            working_set,
+            productions,
+            nodes,
+        })
+    }
+}
+
+impl Memo {
+    /// The number of points in the iteration, including the end of the last line.
+    pub fn len(&self) -> u64 {
+        let lines = self.total().points;
+        if lines == 0 {
+            0
+        } else {
+            lines.saturating_add(1)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Where the turtle finishes.
+    pub fn end(&self, origin: Point2) -> Point2 {
+        let (end, _) = self.total().apply(origin);
+        end
+    }
+
+    /// Turns the iteration into a series of points, as `LSystem::reify_iter` would.
+    pub fn reify_iter(&self, origin: Point2) -> MemoIterator<'_> {
+        MemoIterator {
+            memo: self,
+            origin,
+            turtle: Transform::default(),
+            stack: vec![(self.working_set.iter(), self.nodes.len() - 1)],
+            cached: None,
+            end: self.total().end(origin),
+        }
+    }
+
+    /// The movement of the turtle over the whole iteration.
+    fn total(&self) -> Transform {
+        self.working_set
+            .iter()
+            .fold(Transform::default(), |acc, i| acc.then(&self.top()[*i].transform))
+    }
+
+    /// The nodes of the symbols in the working set.
+    fn top(&self) -> &[Node] {
+        &self.nodes[self.nodes.len() - 1]
+    }
+}
+
+/// Walks the DAG of a memoized iteration, copying the points of each cached node that it reaches.
+pub struct MemoIterator<'a> {
+    memo: &'a Memo,
+    origin: Point2,
+    /// The turtle after the last node that was reached.
+    turtle: Transform,
+    /// The remaining nodes at each level being walked, along with their depth.
+    stack: Vec<(std::slice::Iter<'a, usize>, usize)>,
+    /// The rest of the points of the cached node being copied, and where the turtle was when it started.
+    cached: Option<(std::slice::Iter<'a, Point2>, Transform)>,
+    /// The end of the last line, which is produced once the DAG has been walked.
+    end: Option<Point2>,
+}
+
+impl <'a> Iterator for MemoIterator<'a> {
+    type Item = Point2;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((points, start)) = self.cached.as_mut() {
+                if let Some(pt) = points.next() {
+                    return Some(start.place(*pt, self.origin))
+                }
+                self.cached = None;
+            }
+
+            let (next, depth) = {
+                let (level, depth) = match self.stack.last_mut() {
+                    Some(level) => level,
+                    None => return self.end.take(),
+                };
+                (level.next(), *depth)
+            };
+            match next {
+                Some(i) => {
+                    let node = &self.memo.nodes[depth][*i];
+                    match node.points.as_ref() {
+                        Some(points) => {
+                            self.cached = Some((points.iter(), self.turtle));
+                            self.turtle = self.turtle.then(&node.transform);
+                        }
+                        None => self.stack.push((self.memo.productions[*i].iter(), depth - 1)),
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::l_system::dynamic::DynamicGrammar;
+    use nannou::geom::Point2;
+
+    const DRAGON: &str = "
+        axiom = FX
+        X -> X+YF+
+        Y -> -FX-Y
+    ";
+
+    #[test]
+    fn points_match_expansion() {
+        let lsystem = DynamicGrammar::parse(DRAGON).unwrap().into_lsystem();
+        let origin = Point2 { x: 10.0, y: 5.0 };
+        // Deep enough that the top nodes are too big to cache.
+        let memo = lsystem.memoize(13, std::f32::consts::FRAC_PI_2, 3.0).unwrap();
+        let expected: Vec<Point2> = lsystem
+            .expand_n(13)
+            .reify_iter(std::f32::consts::FRAC_PI_2, 3.0, origin)
+            .collect();
+        let points: Vec<Point2> = memo.reify_iter(origin).collect();
+        assert_eq!(memo.len(), expected.len() as u64);
+        assert_eq!(points.len(), expected.len());
+        points.iter().zip(expected.iter()).for_each(|(a, b)| {
+            assert!((a.x - b.x).abs() < 0.01 && (a.y - b.y).abs() < 0.01, "{:?} != {:?}", a, b);
+        });
+    }
+
+    #[test]
+    fn branches_are_refused() {
+        let lsystem = DynamicGrammar::parse("axiom = F[+F]F").unwrap().into_lsystem();
+        assert!(lsystem.memoize(0, std::f32::consts::FRAC_PI_2, 1.0).is_none());
+
+        // Stack symbols that only show up in later iterations count too.
+        let lsystem = DynamicGrammar::parse("axiom = F\nF -> F[+F]F").unwrap().into_lsystem();
+        assert!(lsystem.memoize(3, std::f32::consts::FRAC_PI_2, 1.0).is_none());
+    }
+
+    #[test]
+    fn deep_iterations_are_cheap() {
+        // The dragon ends at (1 + i)^n when drawn on the complex plane.
+        let lsystem = DynamicGrammar::parse(DRAGON).unwrap().into_lsystem();
+        let memo = lsystem.memoize(60, std::f32::consts::FRAC_PI_2, 1.0).unwrap();
+        assert_eq!(memo.len(), (1 << 60) + 1);
+        let end = memo.end(Point2 { x: 0.0, y: 0.0 });
+        let scale = (1 << 30) as f32;
+        assert!((end.x / scale + 1.0).abs() < 0.001 && (end.y / scale).abs() < 0.001, "{:?}", end);
+    }
+}
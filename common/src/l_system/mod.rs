@@ -0,0 +1,767 @@
+
+pub mod curves;
+pub mod diagnostics;
+pub mod dynamic;
+pub mod fractint;
+pub mod growth;
+pub mod lattice;
+pub mod memo;
+pub mod morph;
+pub mod provenance;
+pub mod random_access;
+pub mod turtle;
+pub mod turtle_3d;
+
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use std::borrow::Borrow;
+use turtle::{Command, Position, Turtle, Turtle2};
+
+/// Lindenmayer system
+#[derive(Clone, Debug)]
+pub struct LSystem<T>
+{
+    working_set: Vec<T>, // TODO add angle here?
+}
+
+impl <T> LSystem<T> {
+    // TODO consider adding an angle parameter and removing it from reify_iter
+    pub fn new(axiom: Vec<T>) -> Self {
+        LSystem {
+            working_set: axiom,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.working_set.len()
+    }
+
+    /// An iterator over the symbols.
+    pub fn iter(&self) -> impl Iterator<Item=&T> {
+        self.working_set.iter()
+    }
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar,
+{
+    /// Advances the system to the next iteration by expanding the existing symbols using the production rules.
+    pub fn iterate(self) -> Self {
+        let working_set = self.working_set
+            .into_iter()
+            .flat_map(T::production_rules)
+            .collect();
+        LSystem {
+            working_set,
+        }
+    }
+
+    /// Advances the system to the next iteration by expanding the existing symbols using the production rules.
+    ///
+    /// This performs the expansion task n times before collecting.
+    /// It should therefore be more flexible and performant than calling `iterate()` multiple times.
+    pub fn iterate_n(self, n: usize) -> Self {
+        let iter: Box<Iterator<Item=T>> = Box::new(self.working_set.into_iter());
+        // TODO I would love a better way to accomplish this, but Boxing is a quick way to erase type info so that I can prevent multiple collections.
+        let i = (0..n)
+            .fold( iter, |i: Box<Iterator<Item=T>>, _| {
+                Box::new(i
+                    .flat_map(T::production_rules)
+                )
+            });
+
+        let working_set = i.collect();
+        Self {
+            working_set
+        }
+    }
+
+    /// Like `iterate_n`, but predicts the size of the final iteration from the growth matrix first,
+    /// so that the collection is allocated once.
+    ///
+    /// Grammars with too many distinct symbols to build a growth matrix for are collected without a prediction.
+    pub fn iterate_n_with_capacity(self, n: usize) -> Self
+    where
+        T: Clone + PartialEq
+    {
+        let capacity = self.growth().map_or(0, |growth| growth.len(n) as usize);
+        let mut working_set = Vec::with_capacity(capacity);
+        working_set.extend(self.expand_n(n));
+        Self {
+            working_set
+        }
+    }
+
+    /// Advances the system to the next iteration,
+    /// using the random number generator to pick between the weighted productions of each symbol.
+    ///
+    /// Using a seeded generator will reproduce the same system every time.
+    pub fn iterate_with_rng<R: Rng>(self, rng: &mut R) -> Self {
+        let working_set = self.working_set
+            .into_iter()
+            .flat_map(|v| choose_production(v, rng))
+            .collect();
+        LSystem {
+            working_set,
+        }
+    }
+
+    /// Advances the system n iterations,
+    /// using the random number generator to pick between the weighted productions of each symbol.
+    pub fn iterate_n_with_rng<R: Rng>(self, n: usize, rng: &mut R) -> Self {
+        (0..n).fold(self, |lsystem, _| lsystem.iterate_with_rng(rng))
+    }
+
+    /// Advances the system to the next iteration,
+    /// expanding each symbol with respect to its left and right neighbours.
+    ///
+    /// Symbols that are ignored in context are skipped over when finding neighbours.
+    /// Branches are also respected: the left context of the first symbol in a branch is the symbol the branch grew from,
+    /// and whole branches are skipped over when looking for the symbol that continues the current branch.
+    pub fn iterate_with_context(self) -> Self
+    where
+        T: Clone
+    {
+        let matching = matching_brackets(&self.working_set);
+        let working_set = (0..self.working_set.len())
+            .flat_map(|i| {
+                let left = left_context(&self.working_set, &matching, i);
+                let right = right_context(&self.working_set, &matching, i);
+                self.working_set[i].clone().context_production_rules(left, right)
+            })
+            .collect();
+        LSystem {
+            working_set,
+        }
+    }
+
+    /// Advances the system n iterations, expanding symbols with respect to their neighbours.
+    pub fn iterate_n_with_context(self, n: usize) -> Self
+    where
+        T: Clone
+    {
+        (0..n).fold(self, |lsystem, _| lsystem.iterate_with_context())
+    }
+
+    /// Expands the symbols n times, yielding the symbols of the final iteration one at a time.
+    ///
+    /// Unlike `iterate_n`, the final iteration is never held in memory,
+    /// so very deep iterations can be reified or written out as they are produced.
+    pub fn expand_n(self, n: usize) -> Expansion<T> {
+        Expansion {
+            depth: n,
+            stack: vec![self.working_set.into_iter()]
+        }
+    }
+
+    /// Turns the working set into a series of points.
+    ///
+    /// These are the start of every line, followed by the end of the last one.
+    pub fn reify_iter(&self, angle: f32, line_length: f32, origin: T::Item) -> impl Iterator<Item=T::Item> + '_ {
+        ReificationIterator::<_, T>::new(self.working_set.iter(), Turtle2::new(origin, angle, line_length))
+    }
+
+    /// Turns the working set into a series of lines, each given by its start and end.
+    pub fn reify_segments_iter(&self, angle: f32, line_length: f32, origin: T::Item) -> impl Iterator<Item=(T::Item, T::Item)> + '_ {
+        let mut turtle = Turtle2::new(origin, angle, line_length);
+        self.working_set
+            .iter()
+            .filter_map(move |v| step(v, &mut turtle).map(|start| (start, turtle.position())))
+    }
+
+    /// Turns the working set into a series of disconnected polylines.
+    ///
+    /// Each time the state stack is popped or the pen is lifted, the current polyline is ended and a new one is started,
+    /// so branches don't get joined together by stray lines when drawn.
+    pub fn reify_branches_iter(&self, angle: f32, line_length: f32, origin: T::Item) -> impl Iterator<Item=Vec<T::Item>> + '_ {
+        BranchingReificationIterator::new(self, Turtle2::new(origin, angle, line_length))
+    }
+}
+
+/// Finds the index of the bracket matching each `Push` or `Pop` symbol.
+fn matching_brackets<T: Grammar>(working_set: &[T]) -> Vec<Option<usize>> {
+    let mut matching = vec![None; working_set.len()];
+    let mut pushes = vec![];
+    working_set.iter().enumerate().for_each(|(i, v)| {
+        match v.stack_op() {
+            Some(StackOp::Push) => pushes.push(i),
+            Some(StackOp::Pop) => {
+                if let Some(push) = pushes.pop() {
+                    matching[push] = Some(i);
+                    matching[i] = Some(push);
+                }
+            }
+            None => {}
+        }
+    });
+    matching
+}
+
+/// Finds the closest symbol preceding the one at the index on its path back to the root.
+fn left_context<'a, T: Grammar>(working_set: &'a [T], matching: &[Option<usize>], index: usize) -> Option<&'a T> {
+    let mut i = index;
+    while i > 0 {
+        i -= 1;
+        let v = &working_set[i];
+        match v.stack_op() {
+            // Skip over a sibling branch entirely.
+            Some(StackOp::Pop) => i = matching[i]?,
+            // Leaving the current branch, so the parent is next.
+            Some(StackOp::Push) => {},
+            None if v.ignored_in_context() => {},
+            None => return Some(v)
+        }
+    }
+    None
+}
+
+/// Finds the closest symbol following the one at the index on the same branch.
+fn right_context<'a, T: Grammar>(working_set: &'a [T], matching: &[Option<usize>], index: usize) -> Option<&'a T> {
+    let mut i = index + 1;
+    while i < working_set.len() {
+        let v = &working_set[i];
+        match v.stack_op() {
+            // Skip over a child branch entirely.
+            Some(StackOp::Push) => i = matching[i]?,
+            // The current branch has ended.
+            Some(StackOp::Pop) => return None,
+            None if v.ignored_in_context() => {},
+            None => return Some(v)
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Reifies the symbol, or has the turtle save or restore its state if the symbol operates on the stack.
+pub(crate) fn step<T: Grammar>(v: &T, turtle: &mut Turtle2<T::Item>) -> Option<T::Item> {
+    match v.stack_op() {
+        Some(StackOp::Push) => {
+            turtle.push();
+            None
+        }
+        Some(StackOp::Pop) => {
+            turtle.pop();
+            None
+        }
+        None => T::reify(v, turtle)
+    }
+}
+
+/// Picks one of the weighted productions for the symbol.
+///
+/// Symbols with a single production don't draw from the random number generator.
+fn choose_production<T: Grammar, R: Rng>(v: T, rng: &mut R) -> Vec<T> {
+    let mut alternatives = v.weighted_production_rules();
+    if alternatives.len() == 1 {
+        return alternatives.pop().map(|(_, production)| production).unwrap_or_default()
+    }
+    let distribution = WeightedIndex::new(alternatives.iter().map(|(weight, _)| *weight))
+        .expect("Production weights should be non-negative and not all zero.");
+    alternatives.swap_remove(distribution.sample(rng)).1
+}
+
+/// Operations on the stack of saved turtle states, used to draw branching structures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackOp {
+    /// Save the current point and angle, conventionally written as `[`.
+    Push,
+    /// Restore the most recently saved point and angle, conventionally written as `]`.
+    Pop,
+}
+
+/// The alphabet of an L-system.
+///
+/// Most alphabets only need to give their productions and the turtle command for each symbol.
+/// Symbols can also carry parameters, such as the length of a line or the size of a turn.
+/// Conditional productions can then be written as match guards on those parameters,
+/// and `reify` can be overridden to move the turtle by them.
+pub trait Grammar: Sized {
+    /// This is the type of point that will be used.
+    type Item: Position;
+
+    /// Rules used to expand a symbol to many other symbols.
+    fn production_rules(self) -> Vec<Self>;
+
+    /// Alternative rules used to expand a symbol, each paired with a relative weight.
+    ///
+    /// One alternative is picked at random when iterating with a random number generator.
+    /// By default, the only alternative is the one given by `production_rules`.
+    fn weighted_production_rules(self) -> Vec<(f32, Vec<Self>)> {
+        vec![(1.0, self.production_rules())]
+    }
+
+    /// What the symbol tells the turtle to do, if anything.
+    fn command(&self) -> Option<Command> {
+        None
+    }
+
+    // TODO Reify is a poor description for what this does, but it sounds cool :/
+
+    /// Reify the symbol to a point,
+    /// the exact location of which can be dependent on prior reified points.
+    ///
+    /// By default, the turtle carries out the symbol's command.
+    fn reify(v: &Self, turtle: &mut Turtle2<Self::Item>) -> Option<Self::Item> {
+        turtle.apply(v.command()?)
+    }
+
+    /// Whether the symbol saves or restores the state used while reifying.
+    ///
+    /// Symbols that have a stack operation are not passed to `reify`.
+    /// By default, this follows the symbol's command.
+    fn stack_op(&self) -> Option<StackOp> {
+        match self.command() {
+            Some(Command::Push) => Some(StackOp::Push),
+            Some(Command::Pop) => Some(StackOp::Pop),
+            _ => None
+        }
+    }
+
+    /// Whether the symbol moves without drawing.
+    ///
+    /// When reifying branches, these symbols end the current polyline.
+    fn lifts_pen(&self) -> bool {
+        self.command() == Some(Command::Move)
+    }
+
+    /// Rules used to expand a symbol given its nearest neighbours when iterating with context.
+    ///
+    /// By default, the neighbours are disregarded and `production_rules` is used.
+    fn context_production_rules(self, _left: Option<&Self>, _right: Option<&Self>) -> Vec<Self> {
+        self.production_rules()
+    }
+
+    /// Whether the symbol is skipped over when finding the neighbours of other symbols.
+    ///
+    /// This is typically true for symbols that only turn.
+    fn ignored_in_context(&self) -> bool {
+        false
+    }
+}
+
+
+
+
+/// A depth-first expansion of an L-system.
+///
+/// Only the productions along the path to the current symbol are held,
+/// so memory use grows with the number of iterations rather than the number of symbols produced.
+pub struct Expansion<T> {
+    depth: usize,
+    /// The remaining symbols at each level of the expansion, starting with the axiom.
+    stack: Vec<std::vec::IntoIter<T>>,
+}
+
+impl <T> Expansion<T>
+where
+    T: Grammar,
+{
+    /// Turns the expanded symbols into a series of points as they are produced.
+    pub fn reify_iter(self, angle: f32, line_length: f32, origin: T::Item) -> impl Iterator<Item=T::Item> {
+        ReificationIterator::<_, T>::new(self, Turtle2::new(origin, angle, line_length))
+    }
+}
+
+impl <T> Iterator for Expansion<T>
+where
+    T: Grammar,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let level = self.stack.len();
+            match self.stack.last_mut()?.next() {
+                // Symbols from the deepest level have been expanded the requested number of times.
+                Some(v) if level > self.depth => return Some(v),
+                Some(v) => self.stack.push(v.production_rules().into_iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// A grammar with a finite set of symbols that can each be written as a character.
+///
+/// The characters follow Fractint's conventions:
+/// `F` and `D` draw, `G` and `M` move without drawing, `+` turns left, `-` turns right,
+/// `|` turns around, and `[` and `]` push and pop the state.
+/// Symbols that do nothing when reified should use other letters.
+pub trait Alphabet: Grammar + Clone {
+    /// Every symbol of the alphabet.
+    fn alphabet() -> Vec<Self>;
+
+    /// The character that represents the symbol.
+    fn to_char(&self) -> char;
+}
+
+
+/// Reifies symbols one at a time, finishing with the end of the last line.
+pub struct ReificationIterator<I, T: Grammar> {
+    inner: I,
+    turtle: Turtle2<T::Item>,
+    /// Where the last line ended, until it is produced after the final symbol.
+    end: Option<T::Item>,
+}
+
+impl <I, T: Grammar> ReificationIterator<I, T>
+{
+    pub fn new(symbols: I, turtle: Turtle2<T::Item>) -> Self {
+        ReificationIterator {
+            inner: symbols,
+            turtle,
+            end: None,
+        }
+    }
+}
+
+
+impl <I, T> Iterator for ReificationIterator<I, T>
+where
+    I: Iterator,
+    I::Item: Borrow<T>,
+    T: Grammar,
+{
+    type Item = T::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Iterate until one of the alphabet elements produces a Some variant.
+        for t in self.inner.by_ref() {
+            if let Some(pt) = step(t.borrow(), &mut self.turtle) {
+                self.end = Some(self.turtle.position());
+                return Some(pt)
+            }
+        }
+        self.end.take()
+    }
+}
+
+
+pub struct BranchingReificationIterator<'a, T, U> {
+    inner: std::slice::Iter<'a, T>,
+    turtle: Turtle2<U>,
+}
+
+impl <'a, T, U> BranchingReificationIterator<'a, T, U>
+{
+    pub fn new(l_system: &'a LSystem<T>, turtle: Turtle2<U>) -> Self {
+        BranchingReificationIterator {
+            inner: l_system.working_set.iter(),
+            turtle,
+        }
+    }
+}
+
+impl <'a, T: 'a> Iterator for BranchingReificationIterator<'a, T, T::Item>
+where
+    T: Grammar,
+{
+    type Item = Vec<T::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut polyline = vec![];
+
+        for t in self.inner.by_ref() {
+            match t.stack_op() {
+                Some(StackOp::Push) => self.turtle.push(),
+                Some(StackOp::Pop) => {
+                    // The end of the branch is only known once the branch has been left.
+                    let end = self.turtle.position();
+                    self.turtle.pop();
+                    if !polyline.is_empty() {
+                        polyline.push(end);
+                        return Some(polyline)
+                    }
+                }
+                None if t.lifts_pen() => {
+                    let end = self.turtle.position();
+                    T::reify(t, &mut self.turtle);
+                    if !polyline.is_empty() {
+                        polyline.push(end);
+                        return Some(polyline)
+                    }
+                }
+                None => {
+                    if let Some(pt) = T::reify(t, &mut self.turtle) {
+                        polyline.push(pt);
+                    }
+                }
+            }
+        }
+
+        if polyline.is_empty() {
+            None
+        } else {
+            polyline.push(self.turtle.position());
+            Some(polyline)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nannou::geom::Point2;
+
+    /// A small branching plant.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Plant {
+        F,
+        Plus,
+        Minus,
+        Push,
+        Pop,
+    }
+
+    impl Grammar for Plant {
+        type Item = Point2;
+
+        fn production_rules(self) -> Vec<Self> {
+            use Plant::*;
+            match self {
+                F => vec![F, Push, Plus, F, Pop, F, Push, Minus, F, Pop, F],
+                other => vec![other]
+            }
+        }
+
+        fn weighted_production_rules(self) -> Vec<(f32, Vec<Self>)> {
+            use Plant::*;
+            match self {
+                F => vec![
+                    (1.0, vec![F, Push, Plus, F, Pop, F, Push, Minus, F, Pop, F]),
+                    (1.0, vec![F, Push, Plus, F, Pop, F]),
+                    (1.0, vec![F, Push, Minus, F, Pop, F]),
+                ],
+                other => vec![(1.0, other.production_rules())]
+            }
+        }
+
+        fn command(&self) -> Option<Command> {
+            use Plant::*;
+            match self {
+                F => Some(Command::Draw),
+                Plus => Some(Command::TurnLeft),
+                Minus => Some(Command::TurnRight),
+                Push => Some(Command::Push),
+                Pop => Some(Command::Pop),
+            }
+        }
+    }
+
+    /// Propagates a signal from left to right.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Signal {
+        A,
+        B,
+        Plus,
+        Push,
+        Pop,
+    }
+
+    impl Grammar for Signal {
+        type Item = Point2;
+
+        fn production_rules(self) -> Vec<Self> {
+            vec![self]
+        }
+
+        fn command(&self) -> Option<Command> {
+            match self {
+                Signal::Push => Some(Command::Push),
+                Signal::Pop => Some(Command::Pop),
+                _ => None
+            }
+        }
+
+        fn context_production_rules(self, left: Option<&Self>, _right: Option<&Self>) -> Vec<Self> {
+            use Signal::*;
+            match (left, self) {
+                (Some(B), A) => vec![B],
+                (_, B) => vec![A],
+                (_, other) => vec![other]
+            }
+        }
+
+        fn ignored_in_context(&self) -> bool {
+            *self == Signal::Plus
+        }
+    }
+
+    fn assert_close(actual: &[Point2], expected: &[(f32, f32)]) {
+        assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+        actual.iter().zip(expected).for_each(|(a, (x, y))| {
+            assert!((a.x - x).abs() < 0.0001 && (a.y - y).abs() < 0.0001, "{:?} != {:?}", actual, expected);
+        });
+    }
+
+    #[test]
+    fn branches_are_disconnected() {
+        let lsystem = LSystem::new(vec![Plant::F]).iterate();
+        let branches: Vec<Vec<Point2>> = lsystem
+            .reify_branches_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+            .collect();
+
+        assert_eq!(branches.len(), 3);
+        assert_close(&branches[0], &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+        assert_close(&branches[1], &[(1.0, 0.0), (2.0, 0.0), (2.0, -1.0)]);
+        assert_close(&branches[2], &[(2.0, 0.0), (3.0, 0.0)]);
+    }
+
+    #[test]
+    fn same_seed_reproduces_system() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let iterate = |seed: u64| -> Vec<Plant> {
+            let mut rng = StdRng::seed_from_u64(seed);
+            LSystem::new(vec![Plant::F])
+                .iterate_n_with_rng(4, &mut rng)
+                .iter()
+                .cloned()
+                .collect()
+        };
+
+        assert_eq!(iterate(7), iterate(7));
+        assert_ne!(iterate(7), iterate(8));
+    }
+
+    #[test]
+    fn single_production_matches_deterministic() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let axiom = vec![Plant::Plus, Plant::Push, Plant::Minus, Plant::Pop];
+        let stochastic: Vec<Plant> = LSystem::new(axiom.clone()).iterate_n_with_rng(3, &mut rng).iter().cloned().collect();
+        let deterministic: Vec<Plant> = LSystem::new(axiom).iterate_n(3).iter().cloned().collect();
+        assert_eq!(stochastic, deterministic);
+    }
+
+    #[test]
+    fn signal_propagates_through_ignored_symbols() {
+        use Signal::*;
+        let lsystem = LSystem::new(vec![B, A, Plus, A, A]);
+        let expected = [
+            vec![A, B, Plus, A, A],
+            vec![A, A, Plus, B, A],
+            vec![A, A, Plus, A, B],
+        ];
+        expected.iter().fold(lsystem, |lsystem, expected| {
+            let lsystem = lsystem.iterate_with_context();
+            assert_eq!(&lsystem.iter().cloned().collect::<Vec<_>>(), expected);
+            lsystem
+        });
+    }
+
+    #[test]
+    fn signal_propagates_into_branches() {
+        use Signal::*;
+        let lsystem = LSystem::new(vec![B, Push, A, Pop, A, Push, Push, A, Pop, A, Pop]).iterate_with_context();
+        assert_eq!(
+            lsystem.iter().cloned().collect::<Vec<_>>(),
+            vec![A, Push, B, Pop, B, Push, Push, A, Pop, A, Pop]
+        );
+        let lsystem = lsystem.iterate_n_with_context(1);
+        assert_eq!(
+            lsystem.iter().cloned().collect::<Vec<_>>(),
+            vec![A, Push, A, Pop, A, Push, Push, B, Pop, B, Pop]
+        );
+    }
+
+    #[test]
+    fn expansion_matches_iteration() {
+        let axiom = vec![Plant::F, Plant::Plus, Plant::F];
+        let expanded: Vec<Plant> = LSystem::new(axiom.clone()).expand_n(3).collect();
+        let iterated: Vec<Plant> = LSystem::new(axiom.clone()).iterate_n(3).iter().cloned().collect();
+        assert_eq!(expanded, iterated);
+
+        let expanded: Vec<Point2> = LSystem::new(axiom.clone())
+            .expand_n(3)
+            .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+            .collect();
+        let iterated: Vec<Point2> = LSystem::new(axiom)
+            .iterate_n(3)
+            .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+            .collect();
+        assert_eq!(expanded, iterated);
+    }
+
+    #[test]
+    fn expansion_of_zero_iterations_is_axiom() {
+        let axiom = vec![Plant::F, Plant::Plus, Plant::F];
+        let expanded: Vec<Plant> = LSystem::new(axiom.clone()).expand_n(0).collect();
+        assert_eq!(expanded, axiom);
+    }
+
+    /// Counts up forever, so there's no end to the symbols it can reach.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Counter(u32);
+
+    impl Grammar for Counter {
+        type Item = Point2;
+
+        fn production_rules(self) -> Vec<Self> {
+            vec![Counter(self.0 + 1), Counter(self.0 + 1)]
+        }
+    }
+
+    #[test]
+    fn unbounded_parameters_iterate() {
+        let lsystem = LSystem::new(vec![Counter(0)]).iterate_n(4);
+        assert_eq!(lsystem.len(), 16);
+        assert!(lsystem.iter().all(|v| *v == Counter(4)));
+    }
+
+    #[test]
+    fn unbounded_parameters_have_no_growth_matrix() {
+        let lsystem = LSystem::new(vec![Counter(0)]);
+        assert!(lsystem.growth().is_none());
+        assert!(lsystem.random_access(4).is_none());
+        assert!(lsystem.memoize(4, 1.0, 1.0).is_none());
+        assert_eq!(lsystem.iterate_n_with_capacity(4).len(), 16);
+    }
+
+    #[test]
+    fn unbranched_is_single_polyline() {
+        let lsystem = LSystem::new(vec![Plant::F, Plant::Plus, Plant::F]);
+        let branches: Vec<Vec<Point2>> = lsystem
+            .reify_branches_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+            .collect();
+
+        assert_eq!(branches.len(), 1);
+        assert_close(&branches[0], &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn points_end_where_the_last_line_does() {
+        let lsystem = LSystem::new(vec![Plant::F, Plant::Plus, Plant::F, Plant::Plus]);
+        let points: Vec<Point2> = lsystem
+            .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+            .collect();
+        assert_close(&points, &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+
+        let segments: Vec<(Point2, Point2)> = lsystem
+            .reify_segments_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+            .collect();
+        assert_eq!(segments.len(), 2);
+        assert_close(&[segments[0].0, segments[0].1], &[(0.0, 0.0), (1.0, 0.0)]);
+        assert_close(&[segments[1].0, segments[1].1], &[(1.0, 0.0), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn segments_skip_branch_returns() {
+        let lsystem = LSystem::new(vec![Plant::F]).iterate();
+        let segments: Vec<(Point2, Point2)> = lsystem
+            .reify_segments_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+            .collect();
+        assert_eq!(segments.len(), 5);
+        segments.iter().for_each(|(start, end)| {
+            let d = *end - *start;
+            assert!((d.x * d.x + d.y * d.y - 1.0).abs() < 0.0001, "{:?}", segments);
+        });
+    }
+}
@@ -0,0 +1,180 @@
+//! Animating the change from one iteration to the next.
+//!
+//! Every line of an iteration grows into the lines of its production in the next one.
+//! A morph starts with the lines of the next iteration laid out along the line they grew from,
+//! evenly spaced, and then moves them to where they belong.
+//! Symbols that don't draw anything, like the `A` and `B` of the Hilbert curve,
+//! grow their lines out of the point where the turtle was.
+
+use super::provenance::Provenance;
+use super::turtle::Turtle2;
+use super::{step, Grammar, LSystem};
+use crate::fit::fit;
+use nannou::geom::{Point2, Vector2};
+
+/// The points of an iteration, paired with where they start out on the iteration before.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Morph {
+    /// The points at the start of the morph, which trace the earlier iteration.
+    pub from: Vec<Point2>,
+    /// The points at the end of the morph, which are the next iteration.
+    pub to: Vec<Point2>,
+}
+
+impl Morph {
+    /// The points part of the way through the morph, where 0 is the start and 1 is the end.
+    pub fn at(&self, t: f32) -> Vec<Point2> {
+        self.from
+            .iter()
+            .zip(self.to.iter())
+            .map(|(from, to)| lerp(*from, *to, t))
+            .collect()
+    }
+
+    /// The same morph played backwards, folding the next iteration back into the earlier one.
+    pub fn reversed(self) -> Self {
+        Morph {
+            from: self.to,
+            to: self.from,
+        }
+    }
+
+    /// Fits the start and end into the window separately,
+    /// so each matches the iteration as it is drawn on its own.
+    pub fn fit(self, dimensions: Vector2, margin: f32) -> Self {
+        Morph {
+            from: fit(self.from, dimensions, margin),
+            to: fit(self.to, dimensions, margin),
+        }
+    }
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar<Item=Point2> + Clone + PartialEq,
+{
+    /// Pairs the points of the next iteration with where they start out on this one.
+    pub fn morph(&self, angle: f32, line_length: f32, origin: Point2) -> Morph {
+        // Where the turtle was before and after each symbol of this iteration.
+        let mut turtle = Turtle2::new(origin, angle, line_length);
+        let spans: Vec<(Point2, Point2)> = self.working_set
+            .iter()
+            .map(|v| {
+                let start = turtle.position();
+                step(v, &mut turtle);
+                (start, turtle.position())
+            })
+            .collect();
+
+        let next = self.clone().annotate().iterate();
+        let (to, provenance): (Vec<Point2>, Vec<Provenance>) = next
+            .reify_iter(angle, line_length, origin)
+            .unzip();
+
+        let parent = |provenance: &Provenance| provenance.parent.expect("Iterated symbols have a parent.");
+        // The end of the last line goes to the end of its parent, so only the starts of lines are spaced out.
+        let last = provenance.len().saturating_sub(1);
+        let mut lines = vec![0usize; spans.len()];
+        provenance[..last].iter().for_each(|p| lines[parent(p)] += 1);
+
+        let mut drawn = vec![0usize; spans.len()];
+        let from = provenance
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let parent = parent(p);
+                let (start, end) = spans[parent];
+                if i == last {
+                    return end
+                }
+                let t = drawn[parent] as f32 / lines[parent] as f32;
+                drawn[parent] += 1;
+                lerp(start, end, t)
+            })
+            .collect();
+
+        Morph {
+            from,
+            to,
+        }
+    }
+}
+
+fn lerp(from: Point2, to: Point2, t: f32) -> Point2 {
+    Point2 {
+        x: from.x * (1.0 - t) + to.x * t,
+        y: from.y * (1.0 - t) + to.y * t,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::l_system::dynamic::DynamicGrammar;
+    use nannou::geom::Point2;
+
+    fn assert_close(a: Point2, b: Point2) {
+        assert!((a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn lines_unfold_from_their_parent() {
+        let lsystem = DynamicGrammar::parse("axiom = F\nangle = 60\nF -> F+F--F+F")
+            .unwrap()
+            .into_lsystem();
+        let origin = Point2 { x: 0.0, y: 0.0 };
+        let morph = lsystem.morph(std::f32::consts::FRAC_PI_3, 1.0, origin);
+
+        let expected: Vec<Point2> = lsystem.clone()
+            .iterate()
+            .reify_iter(std::f32::consts::FRAC_PI_3, 1.0, origin)
+            .collect();
+        assert_eq!(morph.to, expected);
+        assert_eq!(morph.at(1.0), expected);
+
+        assert_eq!(morph.from.len(), 5);
+        morph.from
+            .iter()
+            .zip([0.0, 0.25, 0.5, 0.75, 1.0].iter())
+            .for_each(|(pt, x)| assert_close(*pt, Point2 { x: *x, y: 0.0 }));
+        assert_eq!(morph.at(0.0), morph.from);
+
+        // Both iterations are drawn with the same line length, so the bump grows as it rises out of the line.
+        let peak = Point2 { x: 1.5, y: (3.0f32).sqrt() / 2.0 };
+        assert_close(morph.to[2], peak);
+        assert_close(morph.at(0.5)[2], Point2 { x: 1.0, y: peak.y / 2.0 });
+    }
+
+    #[test]
+    fn lines_grow_out_of_symbols_that_dont_draw() {
+        let lsystem = DynamicGrammar::parse("axiom = A\nA -> +BF-AFA-FB+\nB -> -AF+BFB+FA-")
+            .unwrap()
+            .into_lsystem();
+        let origin = Point2 { x: 2.0, y: 3.0 };
+        let morph = lsystem.morph(std::f32::consts::FRAC_PI_2, 1.0, origin);
+        assert_eq!(morph.from.len(), 4);
+        morph.from.iter().for_each(|pt| assert_close(*pt, origin));
+    }
+
+    #[test]
+    fn the_curve_stays_connected() {
+        let lsystem = DynamicGrammar::parse("axiom = FX\nX -> X+YF+\nY -> -FX-Y")
+            .unwrap()
+            .into_lsystem()
+            .iterate_n(5);
+        let origin = Point2 { x: 0.0, y: 0.0 };
+        let morph = lsystem.morph(std::f32::consts::FRAC_PI_2, 1.0, origin);
+        let points: Vec<Point2> = lsystem.reify_iter(std::f32::consts::FRAC_PI_2, 1.0, origin).collect();
+
+        // Every point of this iteration is also where some point of the morph starts.
+        assert_eq!(morph.from.len(), morph.to.len());
+        points.iter().for_each(|pt| {
+            assert!(morph.from.iter().any(|from| (from.x - pt.x).abs() < 0.001 && (from.y - pt.y).abs() < 0.001));
+        });
+        assert_close(morph.from[morph.from.len() - 1], points[points.len() - 1]);
+
+        let reversed = morph.clone().reversed();
+        assert_eq!(reversed.at(0.0), morph.to);
+        assert_eq!(reversed.at(1.0), morph.from);
+    }
+}
@@ -0,0 +1,183 @@
+//! Tracking where the symbols of an iteration came from.
+//!
+//! Every symbol remembers the iteration that created it and the symbol of the previous iteration that it grew from.
+//! Symbols whose production is just themselves, like turns in most grammars, aren't created anew,
+//! so they keep the generation they were born in.
+
+use super::turtle::Turtle2;
+use super::{step, Grammar, LSystem};
+
+/// Where a symbol came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    /// The iteration that created the symbol, where the axiom is generation 0.
+    pub generation: usize,
+    /// The index of the symbol that this one grew from, in the previous iteration.
+    pub parent: Option<usize>,
+}
+
+/// An L-system whose symbols carry their provenance.
+#[derive(Clone, Debug)]
+pub struct Annotated<T> {
+    working_set: Vec<(T, Provenance)>,
+    /// The number of iterations since the axiom.
+    generation: usize,
+}
+
+impl <T> LSystem<T> {
+    /// Starts tracking provenance, treating the current working set as the axiom.
+    pub fn annotate(self) -> Annotated<T> {
+        let working_set = self.working_set
+            .into_iter()
+            .map(|v| (v, Provenance { generation: 0, parent: None }))
+            .collect();
+        Annotated {
+            working_set,
+            generation: 0,
+        }
+    }
+}
+
+impl <T> Annotated<T> {
+    pub fn len(&self) -> usize {
+        self.working_set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.working_set.is_empty()
+    }
+
+    /// The number of iterations since the axiom.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// An iterator over the symbols and their provenance.
+    pub fn iter(&self) -> impl Iterator<Item=(&T, Provenance)> {
+        self.working_set.iter().map(|(v, provenance)| (v, *provenance))
+    }
+
+    /// Stops tracking provenance.
+    pub fn into_lsystem(self) -> LSystem<T> {
+        LSystem::new(self.working_set.into_iter().map(|(v, _)| v).collect())
+    }
+}
+
+impl <T> Annotated<T>
+where
+    T: Grammar + Clone + PartialEq,
+{
+    /// Advances the system to the next iteration, recording where each new symbol came from.
+    pub fn iterate(self) -> Self {
+        let generation = self.generation + 1;
+        let working_set = self.working_set
+            .into_iter()
+            .enumerate()
+            .flat_map(|(parent, (v, provenance))| {
+                let production = v.clone().production_rules();
+                let unchanged = production.len() == 1 && production[0] == v;
+                let provenance = Provenance {
+                    generation: if unchanged { provenance.generation } else { generation },
+                    parent: Some(parent),
+                };
+                production.into_iter().map(move |v| (v, provenance))
+            })
+            .collect();
+        Annotated {
+            working_set,
+            generation,
+        }
+    }
+
+    /// Advances the system n iterations.
+    pub fn iterate_n(self, n: usize) -> Self {
+        (0..n).fold(self, |annotated, _| annotated.iterate())
+    }
+
+    /// Turns the working set into a series of points, each with the provenance of the symbol that drew it.
+    ///
+    /// As with `LSystem::reify_iter`, the end of the last line comes last,
+    /// and it carries the provenance of the symbol that drew that line.
+    pub fn reify_iter(&self, angle: f32, line_length: f32, origin: T::Item) -> impl Iterator<Item=(T::Item, Provenance)> + '_ {
+        let mut turtle = Turtle2::new(origin, angle, line_length);
+        let mut end = None;
+        self.working_set
+            .iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .filter_map(move |v| match v {
+                Some((v, provenance)) => {
+                    let start = step(v, &mut turtle)?;
+                    end = Some((turtle.position(), *provenance));
+                    Some((start, *provenance))
+                }
+                None => end.take(),
+            })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::l_system::dynamic::DynamicGrammar;
+    use nannou::geom::Point2;
+
+    #[test]
+    fn turns_keep_their_generation() {
+        let annotated = DynamicGrammar::parse("axiom = F\nF -> F+F")
+            .unwrap()
+            .into_lsystem()
+            .annotate()
+            .iterate_n(2);
+        assert_eq!(annotated.generation(), 2);
+        let provenance: Vec<(char, usize, Option<usize>)> = annotated
+            .iter()
+            .map(|(v, provenance)| (v.symbol(), provenance.generation, provenance.parent))
+            .collect();
+        assert_eq!(provenance, vec![
+            ('F', 2, Some(0)),
+            ('+', 2, Some(0)),
+            ('F', 2, Some(0)),
+            ('+', 1, Some(1)),
+            ('F', 2, Some(2)),
+            ('+', 2, Some(2)),
+            ('F', 2, Some(2)),
+        ]);
+    }
+
+    #[test]
+    fn annotation_doesnt_change_the_iteration() {
+        let lsystem = DynamicGrammar::parse("axiom = FX\nX -> X+YF+\nY -> -FX-Y")
+            .unwrap()
+            .into_lsystem();
+        let annotated = lsystem.clone().annotate().iterate_n(6);
+        let iterated = lsystem.iterate_n(6);
+        let origin = Point2 { x: 0.0, y: 0.0 };
+
+        let points: Vec<Point2> = annotated
+            .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, origin)
+            .map(|(pt, _)| pt)
+            .collect();
+        let expected: Vec<Point2> = iterated.reify_iter(std::f32::consts::FRAC_PI_2, 1.0, origin).collect();
+        assert_eq!(points, expected);
+
+        let symbols: Vec<char> = annotated.into_lsystem().iter().map(|v| v.symbol()).collect();
+        let expected: Vec<char> = iterated.iter().map(|v| v.symbol()).collect();
+        assert_eq!(symbols, expected);
+    }
+
+    #[test]
+    fn points_remember_their_symbol() {
+        // Only the G of the axiom is ever rewritten, so the F keeps its generation.
+        let annotated = DynamicGrammar::parse("axiom = FG\ndraw = FG\nG -> GG")
+            .unwrap()
+            .into_lsystem()
+            .annotate()
+            .iterate_n(2);
+        let generations: Vec<usize> = annotated
+            .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2 { x: 0.0, y: 0.0 })
+            .map(|(_, provenance)| provenance.generation)
+            .collect();
+        assert_eq!(generations, vec![0, 2, 2, 2, 2, 2]);
+    }
+}
@@ -0,0 +1,456 @@
+//! Looking up a single symbol or point of an iteration without expanding it.
+//!
+//! The number of symbols that each symbol expands into after d iterations is tabulated for every d up to n.
+//! Finding symbol k is then a walk from the working set down through one production per iteration,
+//! skipping over every expansion that ends before k.
+//! Points are found the same way, using the net movement of the turtle over each skipped expansion.
+
+use super::growth::reachable;
+use super::turtle::{Command, Turtle, Turtle2};
+use super::{step, Expansion, Grammar, LSystem};
+use nannou::geom::Point2;
+use std::ops::Range;
+
+/// Random access into the nth iteration of an L-system.
+#[derive(Clone, Debug)]
+pub struct RandomAccess<T> {
+    /// Every symbol that can be reached from the working set.
+    symbols: Vec<T>,
+    working_set: Vec<usize>,
+    productions: Vec<Vec<usize>>,
+    /// `lengths[d][i]` is the number of symbols that `symbols[i]` expands into after d iterations.
+    lengths: Vec<Vec<u64>>,
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar + Clone + PartialEq,
+{
+    /// Prepares random access into the nth iteration.
+    ///
+    /// This takes time proportional to n and the number of distinct symbols,
+    /// after which each lookup takes time proportional to n.
+    /// Grammars with more than `MAX_SYMBOLS` distinct symbols give `None`.
+    pub fn random_access(&self, n: usize) -> Option<RandomAccess<T>> {
+        let (symbols, working_set, productions) = reachable(&self.working_set)?;
+        let lengths = tabulate(n, &productions, vec![1; symbols.len()], 0, |acc: u64, len| acc.saturating_add(*len));
+        Some(RandomAccess {
+            symbols,
+            working_set,
+            productions,
+            lengths,
+        })
+    }
+}
+
+impl <T> RandomAccess<T>
+where
+    T: Grammar + Clone,
+{
+    /// The number of symbols in the iteration.
+    ///
+    /// Lengths that would overflow saturate at `u64::MAX`, past which symbols can't be found.
+    pub fn len(&self) -> u64 {
+        let depth = self.depth();
+        self.working_set
+            .iter()
+            .fold(0, |acc: u64, i| acc.saturating_add(self.lengths[depth][*i]))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The kth symbol of the iteration.
+    pub fn symbol(&self, k: u64) -> Option<T> {
+        let mut symbol = None;
+        self.descend(k, &self.lengths, |_, level, position| symbol = Some(level[position]));
+        symbol.map(|i| self.symbols[i].clone())
+    }
+
+    /// Expands the iteration starting from its kth symbol.
+    pub fn expand_from(&self, k: u64) -> Expansion<T> {
+        let mut stack = vec![];
+        let found = self.descend(k, &self.lengths, |depth, level, position| {
+            // The symbol containing k is expanded by the next level down, so only the ones after it are left here.
+            let start = if depth > 0 { position + 1 } else { position };
+            let remaining: Vec<T> = level[start..]
+                .iter()
+                .map(|i| self.symbols[*i].clone())
+                .collect();
+            stack.push(remaining.into_iter());
+        });
+        if !found {
+            stack.clear();
+        }
+        Expansion {
+            depth: self.depth(),
+            stack,
+        }
+    }
+
+    /// The number of iterations.
+    fn depth(&self) -> usize {
+        self.lengths.len() - 1
+    }
+
+    /// Walks from the working set down to the kth symbol, counting each symbol by its entry in `weights`.
+    ///
+    /// At each level, `visit` is given the remaining depth, the symbols of the level,
+    /// and the position of the symbol that contains k.
+    /// Returns false if there are fewer than k + 1 symbols.
+    fn descend<F>(&self, k: u64, weights: &[Vec<u64>], mut visit: F) -> bool
+    where
+        F: FnMut(usize, &[usize], usize),
+    {
+        let mut k = k;
+        let mut depth = self.depth();
+        let mut level: &[usize] = &self.working_set;
+        loop {
+            let position = level.iter().position(|i| {
+                let weight = weights[depth][*i];
+                if k < weight {
+                    true
+                } else {
+                    k -= weight;
+                    false
+                }
+            });
+            let position = match position {
+                Some(position) => position,
+                None => return false,
+            };
+            visit(depth, level, position);
+            if depth == 0 {
+                return true;
+            }
+            level = &self.productions[level[position]];
+            depth -= 1;
+        }
+    }
+}
+
+impl <T> RandomAccess<T>
+where
+    T: Grammar<Item=Point2> + Clone,
+{
+    /// Prepares random access to the turtle's points, as `LSystem::reify_iter` would produce them.
+    ///
+    /// Symbols that operate on the stack are skipped,
+    /// so branching systems will give different points than they would when drawn.
+    pub fn turtle(&self, angle: f32, line_length: f32) -> TurtleAccess<'_, T> {
+        let leaves = leaf_transforms(&self.symbols, angle, line_length);
+        let transforms = tabulate(self.depth(), &self.productions, leaves, Transform::default(), |acc, t| acc.then(t));
+        let points = transforms
+            .iter()
+            .map(|row| row.iter().map(|t| t.points).collect())
+            .collect();
+
+        TurtleAccess {
+            access: self,
+            angle,
+            line_length,
+            points,
+            transforms,
+        }
+    }
+}
+
+/// Random access to the points of an iteration.
+#[derive(Clone, Debug)]
+pub struct TurtleAccess<'a, T> {
+    access: &'a RandomAccess<T>,
+    angle: f32,
+    line_length: f32,
+    /// `points[d][i]` is the number of points that the expansion of `symbols[i]` after d iterations produces.
+    points: Vec<Vec<u64>>,
+    /// `transforms[d][i]` is the net movement of the turtle over the expansion of `symbols[i]` after d iterations.
+    transforms: Vec<Vec<Transform>>,
+}
+
+impl <'a, T> TurtleAccess<'a, T>
+where
+    T: Grammar<Item=Point2> + Clone,
+{
+    /// The number of points in the iteration, including the end of the last line.
+    pub fn len(&self) -> u64 {
+        let lines = self.total().points;
+        if lines == 0 {
+            0
+        } else {
+            lines.saturating_add(1)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The kth point of the iteration.
+    pub fn point(&self, k: u64, origin: Point2) -> Option<Point2> {
+        let total = self.total();
+        if k == total.points {
+            return total.end(origin)
+        }
+        self.walk(k, &self.points).map(|turtle| turtle.apply(origin).0)
+    }
+
+    /// The position and heading of the turtle just before the kth symbol.
+    ///
+    /// When k is the number of symbols, this is where the turtle finishes.
+    pub fn state(&self, k: u64, origin: Point2) -> Option<(Point2, f32)> {
+        if k == self.access.len() {
+            return Some(self.total().apply(origin))
+        }
+        self.walk(k, &self.access.lengths).map(|turtle| turtle.apply(origin))
+    }
+
+    /// The points produced by a range of symbols.
+    ///
+    /// Ranges that cover an iteration can be reified separately, on different threads for instance,
+    /// and the points chained together to get those of the whole iteration.
+    pub fn reify_range(&self, symbols: Range<u64>, origin: Point2) -> impl Iterator<Item=Point2> {
+        let start = self.walk(symbols.start, &self.access.lengths).unwrap_or_default();
+        let (pt, heading) = start.apply(origin);
+        let mut turtle = Turtle2::new(pt, self.angle, self.line_length);
+        turtle.turn(heading);
+        turtle.apply(Command::ScaleLength(start.scale as f32));
+        self.access
+            .expand_from(symbols.start)
+            .take(symbols.end.saturating_sub(symbols.start) as usize)
+            .filter_map(move |v| step(&v, &mut turtle))
+    }
+
+    /// The movement of the turtle over the whole iteration.
+    fn total(&self) -> Transform {
+        let depth = self.access.depth();
+        self.access.working_set
+            .iter()
+            .fold(Transform::default(), |acc, i| acc.then(&self.transforms[depth][*i]))
+    }
+
+    /// Moves the turtle over every expansion before the kth symbol, as counted by `weights`.
+    fn walk(&self, k: u64, weights: &[Vec<u64>]) -> Option<Transform> {
+        let mut turtle = Transform::default();
+        let found = self.access.descend(k, weights, |depth, level, position| {
+            level[..position]
+                .iter()
+                .for_each(|i| turtle = turtle.then(&self.transforms[depth][*i]));
+        });
+        if found {
+            Some(turtle)
+        } else {
+            None
+        }
+    }
+}
+
+/// The movement of the turtle over each symbol on its own.
+pub(super) fn leaf_transforms<T>(symbols: &[T], angle: f32, line_length: f32) -> Vec<Transform>
+where
+    T: Grammar<Item=Point2>,
+{
+    symbols
+        .iter()
+        .map(|v| {
+            if v.stack_op().is_some() {
+                return Transform::default()
+            }
+            let mut turtle = Turtle2::new(Point2 { x: 0.0, y: 0.0 }, angle, line_length);
+            let draws = T::reify(v, &mut turtle).is_some();
+            Transform {
+                x: turtle.position().x as f64,
+                y: turtle.position().y as f64,
+                angle: turtle.heading() as f64,
+                scale: (turtle.line_length() / line_length) as f64,
+                points: draws as u64,
+                end: if draws { Some((turtle.position().x as f64, turtle.position().y as f64)) } else { None },
+            }
+        })
+        .collect()
+}
+
+/// The net effect of a run of symbols on a turtle that starts at the origin, facing along the x axis.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Transform {
+    pub(super) x: f64,
+    pub(super) y: f64,
+    pub(super) angle: f64,
+    /// How much the line length has been multiplied by.
+    pub(super) scale: f64,
+    /// The number of lines drawn along the way.
+    pub(super) points: u64,
+    /// Where the last line ended.
+    pub(super) end: Option<(f64, f64)>,
+}
+
+impl Default for Transform {
+    /// Leaves the turtle as it is.
+    fn default() -> Self {
+        Transform {
+            x: 0.0,
+            y: 0.0,
+            angle: 0.0,
+            scale: 1.0,
+            points: 0,
+            end: None,
+        }
+    }
+}
+
+impl Transform {
+    /// The effect of running `self` and then `other`.
+    pub(super) fn then(self, other: &Transform) -> Transform {
+        let (sin, cos) = self.angle.sin_cos();
+        Transform {
+            x: self.x + (other.x * cos - other.y * sin) * self.scale,
+            y: self.y + (other.x * sin + other.y * cos) * self.scale,
+            angle: (self.angle + other.angle) % (std::f64::consts::PI * 2.0),
+            scale: self.scale * other.scale,
+            points: self.points.saturating_add(other.points),
+            end: other.end.map(|(x, y)| self.relative(x, y)).or(self.end),
+        }
+    }
+
+    /// The position and heading of a turtle that started at the origin.
+    pub(super) fn apply(self, origin: Point2) -> (Point2, f32) {
+        let pt = Point2 {
+            x: origin.x + self.x as f32,
+            y: origin.y + self.y as f32,
+        };
+        (pt, self.angle as f32)
+    }
+
+    /// Where a point given relative to the start of a run ends up, for a run that starts here.
+    pub(super) fn place(self, pt: Point2, origin: Point2) -> Point2 {
+        let (x, y) = self.relative(pt.x as f64, pt.y as f64);
+        Point2 {
+            x: origin.x + x as f32,
+            y: origin.y + y as f32,
+        }
+    }
+
+    /// Where the last line of a run starting at the origin ends, if any were drawn.
+    pub(super) fn end(self, origin: Point2) -> Option<Point2> {
+        self.end.map(|(x, y)| Point2 {
+            x: origin.x + x as f32,
+            y: origin.y + y as f32,
+        })
+    }
+
+    /// Moves a point given relative to the start of a run so that it is relative to the start of this one.
+    fn relative(self, x: f64, y: f64) -> (f64, f64) {
+        let (sin, cos) = self.angle.sin_cos();
+        (
+            self.x + (x * cos - y * sin) * self.scale,
+            self.y + (x * sin + y * cos) * self.scale,
+        )
+    }
+}
+
+/// Tabulates a value for every symbol at every depth up to n,
+/// combining the values of each symbol's production from the depth below.
+pub(super) fn tabulate<U, F>(n: usize, productions: &[Vec<usize>], leaves: Vec<U>, empty: U, combine: F) -> Vec<Vec<U>>
+where
+    U: Clone,
+    F: Fn(U, &U) -> U,
+{
+    let mut table = vec![leaves];
+    for depth in 1..=n {
+        let row = productions
+            .iter()
+            .map(|production| {
+                production
+                    .iter()
+                    .fold(empty.clone(), |acc, j| combine(acc, &table[depth - 1][*j]))
+            })
+            .collect();
+        table.push(row);
+    }
+    table
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::l_system::dynamic::{DynamicGrammar, DynamicSymbol};
+    use crate::l_system::LSystem;
+    use nannou::geom::Point2;
+
+    const DRAGON: &str = "
+        axiom = FX
+        X -> X+YF+
+        Y -> -FX-Y
+    ";
+
+    fn dragon() -> LSystem<DynamicSymbol> {
+        DynamicGrammar::parse(DRAGON).unwrap().into_lsystem()
+    }
+
+    fn assert_close(a: Point2, b: Point2, epsilon: f32) {
+        assert!((a.x - b.x).abs() < epsilon && (a.y - b.y).abs() < epsilon, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn symbols_match_iteration() {
+        (0..8).for_each(|n| {
+            let access = dragon().random_access(n).unwrap();
+            let expanded = dragon().iterate_n(n);
+            assert_eq!(access.len(), expanded.len() as u64);
+            expanded.iter().enumerate().for_each(|(k, v)| {
+                assert_eq!(access.symbol(k as u64).as_ref(), Some(v));
+            });
+            assert_eq!(access.symbol(access.len()), None);
+        });
+    }
+
+    #[test]
+    fn expansion_resumes_from_any_symbol() {
+        let access = dragon().random_access(5).unwrap();
+        let expanded: Vec<DynamicSymbol> = dragon().expand_n(5).collect();
+        (0..=expanded.len()).for_each(|k| {
+            let resumed: Vec<DynamicSymbol> = access.expand_from(k as u64).collect();
+            assert_eq!(resumed, &expanded[k..]);
+        });
+    }
+
+    #[test]
+    fn points_match_reification() {
+        let origin = Point2 { x: 3.0, y: -2.0 };
+        let access = dragon().random_access(9).unwrap();
+        let turtle = access.turtle(std::f32::consts::FRAC_PI_2, 2.0);
+        let lsystem = dragon().iterate_n(9);
+        let points: Vec<Point2> = lsystem.reify_iter(std::f32::consts::FRAC_PI_2, 2.0, origin).collect();
+        assert_eq!(turtle.len(), points.len() as u64);
+        points.iter().enumerate().for_each(|(k, pt)| {
+            assert_close(turtle.point(k as u64, origin).unwrap(), *pt, 0.001);
+        });
+        assert_eq!(turtle.point(turtle.len(), origin), None);
+    }
+
+    #[test]
+    fn ranges_join_up() {
+        let origin = Point2 { x: 0.0, y: 0.0 };
+        let access = dragon().random_access(8).unwrap();
+        let turtle = access.turtle(std::f32::consts::FRAC_PI_2, 1.0);
+        let whole: Vec<Point2> = turtle.reify_range(0..access.len(), origin).collect();
+        let chunks: Vec<Point2> = (0..access.len())
+            .step_by(37)
+            .flat_map(|start| turtle.reify_range(start..start + 37, origin))
+            .collect();
+        assert_eq!(whole.len(), chunks.len());
+        whole.iter().zip(chunks.iter()).for_each(|(a, b)| assert_close(*a, *b, 0.001));
+    }
+
+    #[test]
+    fn deep_points_are_found() {
+        // The first half of each iteration of the dragon is the previous iteration,
+        // which ends at (1 + i)^n when drawn on the complex plane.
+        let origin = Point2 { x: 0.0, y: 0.0 };
+        let access = dragon().random_access(41).unwrap();
+        let turtle = access.turtle(std::f32::consts::FRAC_PI_2, 1.0);
+        assert_eq!(turtle.len(), (1 << 41) + 1);
+        let midpoint = turtle.point(1 << 40, origin).unwrap();
+        assert_close(midpoint, Point2 { x: (1 << 20) as f32, y: 0.0 }, 1.0);
+    }
+}
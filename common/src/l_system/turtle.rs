@@ -0,0 +1,165 @@
+//! Turtles, which draw L-systems by carrying out commands one symbol at a time.
+//!
+//! Grammars map their symbols to commands, so the drawing logic lives here instead of in every alphabet.
+//! Turning left is always a positive turn, counterclockwise when the y axis points up.
+
+use crate::point_ext::line_to;
+use nannou::geom::Point2;
+
+/// What a symbol tells the turtle to do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    /// Move forward, drawing a line.
+    Draw,
+    /// Move forward without drawing.
+    Move,
+    TurnLeft,
+    TurnRight,
+    TurnAround,
+    /// Multiply the line length by a factor.
+    ScaleLength(f32),
+    /// Save the turtle's state.
+    Push,
+    /// Restore the most recently saved state.
+    Pop,
+    // Only used in three dimensions.
+    PitchDown,
+    PitchUp,
+    RollLeft,
+    RollRight,
+}
+
+/// Something that carries out commands.
+pub trait Turtle {
+    type Point;
+
+    /// Carries out a command, producing the point that a line was drawn from.
+    fn apply(&mut self, command: Command) -> Option<Self::Point>;
+}
+
+/// A point that a turtle in the plane can move.
+pub trait Position: Copy {
+    /// The point at a distance along the given heading.
+    fn advance(self, heading: f32, distance: f32) -> Self;
+}
+
+impl Position for Point2 {
+    fn advance(self, heading: f32, distance: f32) -> Self {
+        line_to(self, heading, distance)
+    }
+}
+
+/// A turtle in the plane.
+#[derive(Clone, Debug)]
+pub struct Turtle2<U = Point2> {
+    position: U,
+    /// Angle in radians, counterclockwise from the x axis.
+    heading: f32,
+    /// The angle turned by `TurnLeft` and `TurnRight`.
+    angle: f32,
+    line_length: f32,
+    stack: Vec<(U, f32, f32)>,
+}
+
+impl <U: Position> Turtle2<U> {
+    /// A turtle at the origin, facing along the x axis.
+    pub fn new(origin: U, angle: f32, line_length: f32) -> Self {
+        Turtle2 {
+            position: origin,
+            heading: 0.0,
+            angle,
+            line_length,
+            stack: vec![],
+        }
+    }
+
+    pub fn position(&self) -> U {
+        self.position
+    }
+
+    pub fn heading(&self) -> f32 {
+        self.heading
+    }
+
+    /// The angle turned by `TurnLeft` and `TurnRight`.
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    pub fn line_length(&self) -> f32 {
+        self.line_length
+    }
+
+    /// Moves forward by a multiple of the line length, producing the point it started from.
+    pub fn forward(&mut self, scale: f32) -> U {
+        let start = self.position;
+        self.position = self.position.advance(self.heading, self.line_length * scale);
+        start
+    }
+
+    /// Turns left by an angle in radians, or right if it is negative.
+    pub fn turn(&mut self, angle: f32) {
+        self.heading += angle;
+    }
+
+    pub fn push(&mut self) {
+        self.stack.push((self.position, self.heading, self.line_length));
+    }
+
+    /// Restores the most recently saved state, if there is one.
+    pub fn pop(&mut self) {
+        if let Some((position, heading, line_length)) = self.stack.pop() {
+            self.position = position;
+            self.heading = heading;
+            self.line_length = line_length;
+        }
+    }
+}
+
+impl <U: Position> Turtle for Turtle2<U> {
+    type Point = U;
+
+    fn apply(&mut self, command: Command) -> Option<U> {
+        match command {
+            Command::Draw => return Some(self.forward(1.0)),
+            Command::Move => {
+                self.forward(1.0);
+            }
+            Command::TurnLeft => self.turn(self.angle),
+            Command::TurnRight => self.turn(-self.angle),
+            Command::TurnAround => self.turn(std::f32::consts::PI),
+            Command::ScaleLength(factor) => self.line_length *= factor,
+            Command::Push => self.push(),
+            Command::Pop => self.pop(),
+            // Pitching and rolling leave the turtle in the plane.
+            Command::PitchDown | Command::PitchUp | Command::RollLeft | Command::RollRight => {}
+        }
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn left_is_counterclockwise() {
+        let mut turtle = Turtle2::new(Point2 { x: 0.0, y: 0.0 }, std::f32::consts::FRAC_PI_2, 2.0);
+        turtle.apply(Command::TurnLeft);
+        assert_eq!(turtle.apply(Command::Draw), Some(Point2 { x: 0.0, y: 0.0 }));
+        assert!(turtle.position().x.abs() < 0.0001 && (turtle.position().y - 2.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn pop_restores_length() {
+        let mut turtle = Turtle2::new(Point2 { x: 0.0, y: 0.0 }, std::f32::consts::FRAC_PI_2, 2.0);
+        turtle.apply(Command::Push);
+        turtle.apply(Command::ScaleLength(0.5));
+        turtle.apply(Command::Draw);
+        assert!((turtle.position().x - 1.0).abs() < 0.0001);
+        turtle.apply(Command::Pop);
+        assert_eq!(turtle.line_length(), 2.0);
+        assert_eq!(turtle.position(), Point2 { x: 0.0, y: 0.0 });
+    }
+}
@@ -0,0 +1,147 @@
+//! Drawing L-systems in three dimensions.
+//!
+//! The turtle carries a frame of three unit vectors: its heading, its left, and its up.
+//! Turning rotates the frame about the up vector, pitching rotates it about the left vector,
+//! and rolling rotates it about the heading.
+//! The turtle starts out facing along the x axis with its left along the y axis,
+//! so a grammar that only turns is drawn in the xy plane, just as it would be in two dimensions.
+
+use super::turtle::{Command, Turtle};
+use super::{Grammar, LSystem};
+use nannou::geom::{Point3, Vector3};
+
+/// The position and orientation of a turtle in three dimensions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Turtle3 {
+    pub position: Point3,
+    pub heading: Vector3,
+    pub left: Vector3,
+    pub up: Vector3,
+    /// The angle turned by each rotation.
+    angle: f32,
+    line_length: f32,
+    stack: Vec<(Point3, [Vector3; 3], f32)>,
+}
+
+impl Turtle3 {
+    pub fn new(position: Point3, angle: f32, line_length: f32) -> Self {
+        Turtle3 {
+            position,
+            heading: Vector3 { x: 1.0, y: 0.0, z: 0.0 },
+            left: Vector3 { x: 0.0, y: 1.0, z: 0.0 },
+            up: Vector3 { x: 0.0, y: 0.0, z: 1.0 },
+            angle,
+            line_length,
+            stack: vec![],
+        }
+    }
+}
+
+impl Turtle for Turtle3 {
+    type Point = Point3;
+
+    fn apply(&mut self, command: Command) -> Option<Point3> {
+        let cpy = self.position;
+        let angle = self.angle;
+        match command {
+            Command::Draw => {
+                self.position += self.heading * self.line_length;
+                return Some(cpy)
+            }
+            Command::Move => self.position += self.heading * self.line_length,
+            Command::TurnLeft => rotate(&mut self.heading, &mut self.left, angle),
+            Command::TurnRight => rotate(&mut self.heading, &mut self.left, -angle),
+            Command::TurnAround => {
+                self.heading = -self.heading;
+                self.left = -self.left;
+            }
+            Command::PitchUp => rotate(&mut self.heading, &mut self.up, angle),
+            Command::PitchDown => rotate(&mut self.heading, &mut self.up, -angle),
+            Command::RollRight => rotate(&mut self.left, &mut self.up, angle),
+            Command::RollLeft => rotate(&mut self.left, &mut self.up, -angle),
+            Command::ScaleLength(factor) => self.line_length *= factor,
+            Command::Push => {
+                self.stack.push((self.position, [self.heading, self.left, self.up], self.line_length));
+            }
+            Command::Pop => {
+                if let Some((position, [heading, left, up], line_length)) = self.stack.pop() {
+                    self.position = position;
+                    self.heading = heading;
+                    self.left = left;
+                    self.up = up;
+                    self.line_length = line_length;
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Rotates a pair of the turtle's axes within their plane, turning `a` towards `b`.
+fn rotate(a: &mut Vector3, b: &mut Vector3, angle: f32) {
+    let (sin, cos) = angle.sin_cos();
+    let new_a = *a * cos + *b * sin;
+    let new_b = *b * cos - *a * sin;
+    *a = new_a;
+    *b = new_b;
+}
+
+impl <T> LSystem<T>
+where
+    T: Grammar,
+{
+    /// Turns the working set into a series of points in three dimensions, using the command of each symbol.
+    ///
+    /// Like `reify_iter`, these are the start of every line, followed by the end of the last one.
+    pub fn reify_3d_iter(&self, angle: f32, line_length: f32, origin: Point3) -> impl Iterator<Item=Point3> + '_ {
+        let mut turtle = Turtle3::new(origin, angle, line_length);
+        let mut end = None;
+        self.iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .filter_map(move |v| match v {
+                Some(v) => {
+                    let start = turtle.apply(v.command()?)?;
+                    end = Some(turtle.position);
+                    Some(start)
+                }
+                None => end.take(),
+            })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::l_system::dynamic::DynamicGrammar;
+    use nannou::geom::Point2;
+
+    #[test]
+    fn flat_grammars_stay_in_the_plane() {
+        let lsystem = DynamicGrammar::parse("axiom = F\nF -> F+F--F+F\nangle = 60")
+            .unwrap()
+            .into_lsystem()
+            .iterate_n(3);
+        let flat: Vec<Point2> = lsystem.reify_iter(std::f32::consts::FRAC_PI_3, 1.0, Point2 { x: 0.0, y: 0.0 }).collect();
+        let points: Vec<Point3> = lsystem.reify_3d_iter(std::f32::consts::FRAC_PI_3, 1.0, Point3 { x: 0.0, y: 0.0, z: 0.0 }).collect();
+        assert_eq!(flat.len(), points.len());
+        flat.iter().zip(points.iter()).for_each(|(a, b)| {
+            assert!((a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001 && b.z == 0.0);
+        });
+    }
+
+    #[test]
+    fn frame_rotates() {
+        let mut turtle = Turtle3::new(Point3 { x: 0.0, y: 0.0, z: 0.0 }, std::f32::consts::FRAC_PI_2, 1.0);
+        // Facing up, with the turtle's back to the x axis.
+        turtle.apply(Command::PitchUp);
+        assert!((turtle.heading.z - 1.0).abs() < 0.001 && (turtle.up.x + 1.0).abs() < 0.001);
+        // Rolling right lifts the left side up, which is now towards negative x.
+        turtle.apply(Command::RollRight);
+        assert!((turtle.left.x + 1.0).abs() < 0.001);
+        turtle.apply(Command::TurnLeft);
+        turtle.apply(Command::Draw);
+        assert!((turtle.position.x + 1.0).abs() < 0.001);
+    }
+}
@@ -3,4 +3,5 @@ pub mod fft;
 pub mod l_system;
 pub mod collinear;
 pub mod draw;
+pub mod fit;
 pub mod point_ext;
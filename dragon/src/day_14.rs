@@ -3,8 +3,10 @@
 use nannou::prelude::*;
 use std::num::Wrapping;
 use common::l_system::{LSystem};
+use common::l_system::morph::Morph;
 use crate::dragon::Dragon;
 use common::collinear::{condense_collinear};
+use common::fit::fit;
 use common::draw::{colored_lines_no_corners, rainbow};
 
 pub struct Model {
@@ -13,45 +15,83 @@ pub struct Model {
     frame_counter: Wrapping<usize>,
     /// Buffer containing all of the lines needed to draw the complete curve for the current iteration.
     point_buffer: Vec<Point2>,
+    /// The change to the current iteration that is being animated, and how many frames it has been running.
+    morph: Option<(Morph, usize)>,
     iteration: usize,
     thickness: f32,
 }
 
 
 const ITERATION: usize = 4;
-const ITERATION_LIMIT: usize = 19;
+/// The most lines that will be drawn before refusing to advance to the next iteration.
+const LINE_BUDGET: u64 = 1 << 20;
+/// The most lines that a change of iteration is animated for.
+/// Morphs are built from a full expansion rather than the memoized one, so bigger changes happen at once.
+const MORPH_BUDGET: u64 = 1 << 14;
 const TITLE: &str = "day 14";
+const WINDOW_SIZE: u32 = 512;
+/// How long it takes for a change of iteration to unfold.
+const MORPH_FRAMES: usize = 30;
+const MARGIN: f32 = 20.0;
 const INITIAL_THICKNESS: f32 = 1.0;
 const THICKNESS_STEP: f32 = 0.25;
 
 
-fn build_point_buffer(iterations: usize) -> Vec<Point2> {
+fn axiom() -> Vec<Dragon> {
     use Dragon::*;
-    let axiom = vec![F, X];
-    let lsystem = LSystem::new(axiom).iterate_n(iterations);
-    let line_length: f32 = 200.0 / (2.0 * (iterations as f32).powf(1.6));
-
+    vec![F, X]
+}
 
-    let origin = Point2::default();
+fn build_point_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Point2> {
+    let axiom = axiom();
 
-    let point_buffer: Vec<Point2> = lsystem
-        .reify_iter(std::f32::consts::FRAC_PI_2 , line_length, origin)
+    // Repeated parts of the curve are only expanded once, and their points copied into place.
+    let point_buffer: Vec<Point2> = LSystem::new(axiom)
+        .memoize(iterations, std::f32::consts::FRAC_PI_2, 1.0)
+        .expect("The dragon has a few symbols and doesn't branch.")
+        .reify_iter(Point2::default())
         .collect();
 
     let point_buffer = condense_collinear(point_buffer);
 
-    point_buffer
+    fit(point_buffer, window_dimensions, MARGIN)
 }
 
+/// Unfolds each line of an iteration into its production in the next one.
+fn build_morph(iteration: usize, window_dimensions: Vector2) -> Morph {
+    LSystem::new(axiom())
+        .iterate_n(iteration)
+        .morph(std::f32::consts::FRAC_PI_2, 1.0, Point2::default())
+        .fit(window_dimensions, MARGIN)
+}
 
+/// A frame of the morph, condensed the same way as the point buffer so the last frame matches it.
+fn morph_frame(morph: &Morph, t: f32) -> Vec<Point2> {
+    condense_collinear(morph.at(t))
+}
 
+/// Moves to an adjacent iteration, unfolding or folding the lines if there are few enough to animate.
+fn change_iteration(model: &mut Model, iteration: usize) {
+    let (smaller, larger) = (iteration.min(model.iteration), iteration.max(model.iteration));
+    model.morph = if LSystem::new(axiom()).growth().map_or(false, |growth| growth.drawing_len(larger) <= MORPH_BUDGET) {
+        let morph = build_morph(smaller, model.window_dimensions);
+        let morph = if iteration > model.iteration { morph } else { morph.reversed() };
+        Some((morph, 0))
+    } else {
+        None
+    };
+    model.iteration = iteration;
+    if model.morph.is_none() {
+        model.point_buffer = build_point_buffer(iteration, model.window_dimensions);
+    }
+}
 
 
 impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title(TITLE)
             .view(view)
             .event(event)
@@ -59,13 +99,15 @@ impl Model {
             .build()
             .unwrap();
 
-        let point_buffer = build_point_buffer(ITERATION);
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(ITERATION, window_dimensions);
 
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             point_buffer,
+            morph: None,
             iteration: ITERATION,
             thickness: INITIAL_THICKNESS
         }
@@ -73,11 +115,22 @@ impl Model {
 
     pub fn update(_app: &App, model: &mut Model, _update: Update) {
         model.frame_counter += Wrapping(1);
+        if let Some((morph, frame)) = model.morph.as_mut() {
+            *frame += 1;
+            if *frame < MORPH_FRAMES {
+                model.point_buffer = morph_frame(morph, *frame as f32 / MORPH_FRAMES as f32);
+            } else {
+                model.morph = None;
+                model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions);
+            }
+        }
     }
 }
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.morph = None;
+    model.point_buffer = build_point_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -90,16 +143,16 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
         WindowEvent::KeyPressed(key) => {
             match key {
                 Key::Right => {
-                    if model.iteration < ITERATION_LIMIT {
-                        model.iteration += 1;
+                    if LSystem::new(axiom()).growth().map_or(false, |growth| growth.drawing_len(model.iteration + 1) <= LINE_BUDGET) {
+                        let iteration = model.iteration + 1;
+                        change_iteration(model, iteration);
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
                 },
                 Key::Left => {
                     if model.iteration > 0 {
-                        model.iteration -= 1;
+                        let iteration = model.iteration - 1;
+                        change_iteration(model, iteration);
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
                 }
                 Key::Up => {
                     model.thickness += THICKNESS_STEP;
@@ -144,6 +197,7 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
         17 => skip * 2400,
         18 => skip * 5000,
         19 => skip * 10000,
+        20 => skip * 20000,
         _ => skip
     };
 
@@ -5,6 +5,7 @@ use std::num::Wrapping;
 use common::l_system::{LSystem};
 use crate::terdragon::TerDragon;
 use common::collinear::{condense_collinear};
+use common::fit::fit;
 use common::draw::{colored_lines_no_corners, rainbow};
 
 pub struct Model {
@@ -19,28 +20,31 @@ pub struct Model {
 
 
 const ITERATION: usize = 4;
-const ITERATION_LIMIT: usize = 19;
+const LINE_BUDGET: u64 = 1 << 20;
 const TITLE: &str = "day 16";
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 20.0;
 const INITIAL_THICKNESS: f32 = 1.0;
 const THICKNESS_STEP: f32 = 0.25;
 
 
-fn build_point_buffer(iterations: usize) -> Vec<Point2> {
+fn axiom() -> Vec<TerDragon> {
     use TerDragon::*;
-    let axiom = vec![F];
-    let lsystem = LSystem::new(axiom).iterate_n(iterations);
-    let line_length: f32 = 500.0 / (2.0 * (iterations as f32).powf(2.2));
-
+    vec![F]
+}
 
-    let origin = Point2::default();
+fn build_point_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Point2> {
+    let axiom = axiom();
 
-    let point_buffer: Vec<Point2> = lsystem
-        .reify_iter(2.0 * std::f32::consts::FRAC_PI_3 , line_length, origin)
+    let point_buffer: Vec<Point2> = LSystem::new(axiom)
+        .memoize(iterations, 2.0 * std::f32::consts::FRAC_PI_3, 1.0)
+        .expect("The terdragon has a few symbols and doesn't branch.")
+        .reify_iter(Point2::default())
         .collect();
 
     let point_buffer = condense_collinear(point_buffer);
 
-    point_buffer
+    fit(point_buffer, window_dimensions, MARGIN)
 }
 
 
@@ -51,7 +55,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title(TITLE)
             .view(view)
             .event(event)
@@ -59,11 +63,12 @@ impl Model {
             .build()
             .unwrap();
 
-        let point_buffer = build_point_buffer(ITERATION);
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(ITERATION, window_dimensions);
 
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             point_buffer,
             iteration: ITERATION,
@@ -78,6 +83,7 @@ impl Model {
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.point_buffer = build_point_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -90,16 +96,16 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
         WindowEvent::KeyPressed(key) => {
             match key {
                 Key::Right => {
-                    if model.iteration < ITERATION_LIMIT {
+                    if LSystem::new(axiom()).growth().map_or(false, |growth| growth.drawing_len(model.iteration + 1) <= LINE_BUDGET) {
                         model.iteration += 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 },
                 Key::Left => {
                     if model.iteration > 0 {
                         model.iteration -= 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 }
                 Key::Up => {
                     model.thickness += THICKNESS_STEP;
@@ -0,0 +1,134 @@
+//! Unfolding a strip of paper into a dragon curve.
+
+use nannou::prelude::*;
+use std::num::Wrapping;
+use crate::folding::unfold;
+use common::fit::fit;
+use common::draw::{colored_lines_no_corners, rainbow};
+
+pub struct Model {
+    _window: WindowId,
+    window_dimensions: Vector2,
+    frame_counter: Wrapping<usize>,
+    /// Buffer containing the strip as it is partway through unfolding.
+    point_buffer: Vec<Point2>,
+    /// How many times the strip is unfolded before starting over.
+    folds: usize,
+    thickness: f32,
+}
+
+
+const FOLDS: usize = 8;
+const MAX_FOLDS: usize = 16;
+/// How long it takes for a fold to open.
+const FOLD_FRAMES: usize = 45;
+/// How long the fully unfolded curve is shown before starting over.
+const PAUSE_FRAMES: usize = 90;
+const TITLE: &str = "day 19";
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 20.0;
+const INITIAL_THICKNESS: f32 = 1.0;
+const THICKNESS_STEP: f32 = 0.25;
+
+
+/// Finds how far the strip has been unfolded at the given frame of the animation.
+fn build_point_buffer(frame: usize, folds: usize, window_dimensions: Vector2) -> Vec<Point2> {
+    let frame = frame % (folds * FOLD_FRAMES + PAUSE_FRAMES);
+    let (fold, fraction) = if frame < folds * FOLD_FRAMES {
+        (frame / FOLD_FRAMES, (frame % FOLD_FRAMES) as f32 / FOLD_FRAMES as f32)
+    } else {
+        (folds - 1, 1.0)
+    };
+
+    fit(unfold(fold, fraction), window_dimensions, MARGIN)
+}
+
+
+impl Model {
+    pub fn init(app: &App) -> Model {
+        let _window = app
+            .new_window()
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
+            .with_title(TITLE)
+            .view(view)
+            .event(event)
+            .resized(on_resize)
+            .build()
+            .unwrap();
+
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(0, FOLDS, window_dimensions);
+
+        Model {
+            _window,
+            window_dimensions,
+            frame_counter: Wrapping(0),
+            point_buffer,
+            folds: FOLDS,
+            thickness: INITIAL_THICKNESS
+        }
+    }
+
+    pub fn update(_app: &App, model: &mut Model, _update: Update) {
+        model.frame_counter += Wrapping(1);
+        model.point_buffer = build_point_buffer(model.frame_counter.0, model.folds, model.window_dimensions);
+    }
+}
+
+fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
+    model.window_dimensions = dimensions;
+    model.point_buffer = build_point_buffer(model.frame_counter.0, model.folds, dimensions);
+}
+
+/// Handle events related to the window and update the model if necessary
+fn event(_app: &App, model: &mut Model, event: WindowEvent) {
+    match event {
+        WindowEvent::MouseMoved(_point) => {
+        }
+        WindowEvent::MousePressed(_) => {
+        }
+        WindowEvent::KeyPressed(key) => {
+            match key {
+                Key::Right => {
+                    if model.folds < MAX_FOLDS {
+                        model.folds += 1;
+                    }
+                    model.frame_counter = Wrapping(0);
+                },
+                Key::Left => {
+                    if model.folds > 1 {
+                        model.folds -= 1;
+                    }
+                    model.frame_counter = Wrapping(0);
+                }
+                Key::Up => {
+                    model.thickness += THICKNESS_STEP;
+                }
+                Key::Down => {
+                    model.thickness -= THICKNESS_STEP;
+                }
+                Key::Q => {
+                    std::process::exit(0); // Q -> exit program
+                }
+                _ => {}
+            }
+
+        }
+        _ => {}
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) -> Frame {
+    let draw = app.draw();
+
+    frame.clear(DARK_CHARCOAL);
+
+    // The colors stay put along the strip, so each half can be followed as it swings out.
+    let gradient = rainbow();
+    colored_lines_no_corners(&draw, &model.point_buffer, model.thickness, 0, gradient);
+
+    // Write to the window frame.
+    draw.to_frame(app, &frame).unwrap();
+    // Return the drawn frame.
+    frame
+}
@@ -1,10 +1,10 @@
-use common::l_system::Grammar;
+use common::l_system::{Alphabet, Grammar};
+use common::l_system::turtle::Command;
 use nannou::geom::Point2;
-use common::point_ext::line_to;
 
 ///
 /// Angle should be 90 deg
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Dragon {
     F,
     X,
@@ -28,20 +28,71 @@ impl Grammar for Dragon {
         }
     }
 
-    fn reify(v: &Self, current_pt: &mut Self::Item, current_angle: &mut f32, angle_step: f32, line_length: f32) -> Option<Self::Item>
-    {
+    fn command(&self) -> Option<Command> {
         use Dragon::*;
-        let cpy = current_pt.clone();
-        match v {
-            F => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            X | Y => {},
-            Plus => *current_angle += angle_step,
-            Minus => *current_angle -= angle_step,
-        };
-        match v {
-            F => Some(cpy),
-            Plus | Minus | X  | Y => None
+        match self {
+            F => Some(Command::Draw),
+            Plus => Some(Command::TurnLeft),
+            Minus => Some(Command::TurnRight),
+            X | Y => None
         }
     }
 }
 
+impl Alphabet for Dragon {
+    fn alphabet() -> Vec<Self> {
+        use Dragon::*;
+        vec![F, X, Y, Plus, Minus]
+    }
+
+    fn to_char(&self) -> char {
+        use Dragon::*;
+        match self {
+            F => 'F',
+            X => 'X',
+            Y => 'Y',
+            Plus => '+',
+            Minus => '-'
+        }
+    }
+}
+
+#[test]
+fn fractint_export() {
+    use common::l_system::dynamic::DynamicGrammar;
+    use common::l_system::fractint;
+    use Dragon::*;
+    let grammar = DynamicGrammar::from_alphabet(&[F, X], std::f32::consts::FRAC_PI_2);
+    assert_eq!(fractint::write("Dragon", &grammar).unwrap(), "Dragon {\n  Angle 4\n  Axiom FX\n  X=X+YF+\n  Y=-FX-Y\n}\n");
+}
+
+#[test]
+fn ends_at_a_power_of_one_plus_i() {
+    use common::l_system::LSystem;
+    use Dragon::*;
+    // (1 + i)^10 = (2i)^5 = 32i
+    let lsystem = LSystem::new(vec![F, X]).iterate_n(10);
+    let end = lsystem
+        .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2 { x: 0.0, y: 0.0 })
+        .last()
+        .unwrap();
+    assert!(end.x.abs() < 0.01 && (end.y - 32.0).abs() < 0.01, "{:?}", end);
+}
+
+#[test]
+fn matches_the_catalog() {
+    use common::l_system::{curves, LSystem};
+    use Dragon::*;
+    let origin = Point2 { x: 0.0, y: 0.0 };
+    let curve = curves::by_name("dragon").unwrap();
+    let expected: Vec<Point2> = LSystem::new(vec![F, X])
+        .iterate_n(8)
+        .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, origin)
+        .collect();
+    let points: Vec<Point2> = curve.grammar()
+        .into_lsystem()
+        .iterate_n(8)
+        .reify_iter(curve.grammar().angle(), 1.0, origin)
+        .collect();
+    assert_eq!(points, expected);
+}
@@ -0,0 +1,85 @@
+//! The Heighway dragon as a strip of paper.
+//!
+//! Folding a strip in half over and over, always in the same direction,
+//! and then opening every fold out to a right angle gives the dragon curve.
+//! Going the other way, a strip is unfolded by taking the curve drawn so far,
+//! copying it back on top of itself, and swinging the copy out about the end of the curve, where the fold is.
+//! Swinging it a full quarter turn clockwise gives the next iteration of the `Dragon` L-system.
+
+use nannou::geom::Point2;
+
+/// Opens a strip up about the fold at its end.
+///
+/// The fraction is how far the copy has swung out,
+/// where 0 leaves it lying on top of the strip and 1 is a full quarter turn.
+/// The result has the points of the strip followed by the points of the copy, back to its start.
+pub fn unfold_once(points: &[Point2], fraction: f32) -> Vec<Point2> {
+    let fold = match points.last() {
+        Some(fold) => *fold,
+        None => return vec![],
+    };
+    let angle = -fraction * std::f32::consts::FRAC_PI_2;
+    let (sin, cos) = angle.sin_cos();
+
+    let copy = points
+        .iter()
+        .rev()
+        .skip(1)
+        .map(|pt| {
+            let x = pt.x - fold.x;
+            let y = pt.y - fold.y;
+            Point2 {
+                x: fold.x + x * cos - y * sin,
+                y: fold.y + x * sin + y * cos,
+            }
+        });
+    points.iter().cloned().chain(copy).collect()
+}
+
+/// Unfolds a strip of unit length that starts at the origin, heading along the x axis.
+///
+/// The strip is fully unfolded the given number of times, and then the next fold is opened by the fraction.
+/// With a fraction of 1, this is the same as the next iteration of `Dragon`, starting from `FX`.
+pub fn unfold(folds: usize, fraction: f32) -> Vec<Point2> {
+    let strip = vec![Point2 { x: 0.0, y: 0.0 }, Point2 { x: 1.0, y: 0.0 }];
+    let unfolded = (0..folds).fold(strip, |points, _| unfold_once(&points, 1.0));
+    unfold_once(&unfolded, fraction)
+}
+
+
+#[test]
+fn full_turns_match_the_lsystem() {
+    use common::l_system::LSystem;
+    use crate::dragon::Dragon::*;
+    (0..10).for_each(|n| {
+        let expected: Vec<Point2> = LSystem::new(vec![F, X])
+            .iterate_n(n + 1)
+            .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2 { x: 0.0, y: 0.0 })
+            .collect();
+        let points = unfold(n, 1.0);
+        assert_eq!(points.len(), expected.len());
+        points.iter().zip(expected.iter()).for_each(|(a, b)| {
+            assert!((a.x - b.x).abs() < 0.01 && (a.y - b.y).abs() < 0.01, "{:?} != {:?} at {}", a, b, n);
+        });
+    });
+}
+
+#[test]
+fn folded_strips_lie_on_top_of_themselves() {
+    let points = unfold(3, 0.0);
+    let len = points.len();
+    assert_eq!(len, 17);
+    (0..len / 2).for_each(|i| {
+        let (a, b) = (points[i], points[len - 1 - i]);
+        assert!((a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001, "{:?} != {:?}", a, b);
+    });
+}
+
+#[test]
+fn halfway_is_a_diagonal() {
+    // A single fold, opened to 45 degrees.
+    let points = unfold(0, 0.5);
+    let end = points[2];
+    let expected = 1.0 - std::f32::consts::FRAC_1_SQRT_2;
+    assert!((end.x - expected).abs() < 0.001 && (end.y - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.001, "{:?}", end);
+}
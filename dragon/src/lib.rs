@@ -1,6 +1,8 @@
 
-mod dragon;
-mod terdragon;
+pub mod dragon;
+pub mod folding;
+pub mod terdragon;
 pub mod day_14;
 pub mod day_15;
 pub mod day_16;
+pub mod day_19;
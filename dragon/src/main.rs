@@ -1,7 +1,7 @@
-use dragon::day_16;
+use dragon::day_19;
 
 fn main() {
-    nannou::app(day_16::Model::init)
-        .update(day_16::Model::update)
+    nannou::app(day_19::Model::init)
+        .update(day_19::Model::update)
         .run();
 }
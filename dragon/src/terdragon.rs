@@ -1,10 +1,10 @@
-use common::l_system::Grammar;
-use common::point_ext::line_to;
+use common::l_system::{Alphabet, Grammar};
+use common::l_system::turtle::Command;
 use nannou::geom::Point2;
 
 ///
 /// Angle should be 120 deg
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TerDragon {
     F,
     Plus,
@@ -24,20 +24,53 @@ impl Grammar for TerDragon {
         }
     }
 
-    fn reify(v: &Self, current_pt: &mut Self::Item, current_angle: &mut f32, angle_step: f32, line_length: f32) -> Option<Self::Item>
-    {
+    fn command(&self) -> Option<Command> {
         use TerDragon::*;
-        let cpy = current_pt.clone();
-        match v {
-            F => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            Plus => *current_angle += angle_step,
-            Minus => *current_angle -= angle_step,
-        };
-        match v {
-            F => Some(cpy),
-            Plus | Minus => None
+        match self {
+            F => Some(Command::Draw),
+            Plus => Some(Command::TurnLeft),
+            Minus => Some(Command::TurnRight)
+        }
+    }
+}
+
+impl Alphabet for TerDragon {
+    fn alphabet() -> Vec<Self> {
+        use TerDragon::*;
+        vec![F, Plus, Minus]
+    }
+
+    fn to_char(&self) -> char {
+        use TerDragon::*;
+        match self {
+            F => 'F',
+            Plus => '+',
+            Minus => '-'
         }
     }
 }
 
+#[test]
+fn fractint_export() {
+    use common::l_system::dynamic::DynamicGrammar;
+    use common::l_system::fractint;
+    use TerDragon::*;
+    let grammar = DynamicGrammar::from_alphabet(&[F], 2.0 * std::f32::consts::FRAC_PI_3);
+    assert_eq!(fractint::write("TerDragon", &grammar).unwrap(), "TerDragon {\n  Angle 3\n  Axiom F\n  F=F+F-F\n}\n");
+}
 
+#[test]
+fn ends_at_a_power_of_root_three() {
+    use common::l_system::LSystem;
+    // The first iteration ends at (3/2, √3/2), √3 away at 30 degrees,
+    // and every iteration after it scales and turns the end by the same amount.
+    let n = 6;
+    let end = LSystem::new(vec![TerDragon::F])
+        .iterate_n(n)
+        .reify_iter(2.0 * std::f32::consts::FRAC_PI_3, 1.0, Point2 { x: 0.0, y: 0.0 })
+        .last()
+        .unwrap();
+    let distance = 3.0f32.sqrt().powi(n as i32);
+    let turn = std::f32::consts::FRAC_PI_6 * n as f32;
+    assert!((end.x - distance * turn.cos()).abs() < 0.01 && (end.y - distance * turn.sin()).abs() < 0.01, "{:?}", end);
+}
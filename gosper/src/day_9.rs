@@ -5,6 +5,8 @@
 use nannou::prelude::*;
 use std::num::Wrapping;
 use common::l_system::{LSystem};
+use common::l_system::morph::Morph;
+use common::fit::fit;
 use crate::gosper::Gosper;
 
 pub struct Model {
@@ -13,6 +15,8 @@ pub struct Model {
     frame_counter: Wrapping<usize>,
     /// Buffer containing all of the lines needed to draw the complete curve for the current iteration.
     point_buffer: Vec<Point2>,
+    /// The change to the current iteration that is being animated, and how many frames it has been running.
+    morph: Option<(Morph, usize)>,
     iteration: usize,
     thickness: f32,
 
@@ -21,37 +25,29 @@ pub struct Model {
 
 const ITERATION: usize = 4;
 const INITIAL_THICKNESS: f32 = 2.0;
-const INITIAL_LINE_LENGTH: f32 = 8.0;
+const WINDOW_SIZE: u32 = 512;
+/// How long it takes for a change of iteration to unfold.
+const MORPH_FRAMES: usize = 30;
+const MARGIN: f32 = 20.0;
 
 
-/// Uses an L-system + Gosper grammar to construct a list of items.
-/// Since the l system is reified with points starting at the center,
-/// the path is not centered on the screen.
-///
-/// So an approximate spacial center of the path is found, and the points are all then offset from that.
-fn build_point_buffer(iterations: usize, line_length: f32,) -> Vec<Point2> {
+/// Uses an L-system + Gosper grammar to construct a list of items,
+/// which are then centered in the window and scaled to fill it.
+fn build_point_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Point2> {
     let lsystem = LSystem::new(vec![Gosper::A]).iterate_n(iterations);
     let point_buffer: Vec<Point2> = lsystem
-        .reify_iter(std::f32::consts::FRAC_PI_3, line_length, Point2::default())
+        .reify_iter(std::f32::consts::FRAC_PI_3, 1.0, Point2::default())
         .collect();
 
-    let len = if point_buffer.len() > 0 {
-        point_buffer.len()
-    } else {
-        1
-    };
-
-    let index = (len * 3/ 8) - 1;
-    let center = point_buffer.get(index).cloned().unwrap_or_default();
-
-    let point_buffer: Vec<Point2> = point_buffer
-        .into_iter()
-        .map(|pt| {
-            pt - center
-        })
-        .collect();
+    fit(point_buffer, window_dimensions, MARGIN)
+}
 
-    point_buffer
+/// Unfolds each line of an iteration into its production in the next one.
+fn build_morph(iteration: usize, window_dimensions: Vector2) -> Morph {
+    LSystem::new(vec![Gosper::A])
+        .iterate_n(iteration)
+        .morph(std::f32::consts::FRAC_PI_3, 1.0, Point2::default())
+        .fit(window_dimensions, MARGIN)
 }
 
 
@@ -59,7 +55,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title("day 9")
             .view(view) // The function that will be called for presenting graphics to a frame.
             .event(event) // The function that will be called when the window receives events.
@@ -67,13 +63,15 @@ impl Model {
             .build()
             .unwrap();
 
-        let point_buffer = build_point_buffer(ITERATION, INITIAL_LINE_LENGTH);
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(ITERATION, window_dimensions);
 
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             point_buffer,
+            morph: None,
             iteration: ITERATION,
             thickness: INITIAL_THICKNESS,
         }
@@ -81,11 +79,22 @@ impl Model {
 
     pub fn update(_app: &App, model: &mut Model, _update: Update) {
         model.frame_counter += Wrapping(1);
+        if let Some((morph, frame)) = model.morph.as_mut() {
+            *frame += 1;
+            if *frame < MORPH_FRAMES {
+                model.point_buffer = morph.at(*frame as f32 / MORPH_FRAMES as f32);
+            } else {
+                model.morph = None;
+                model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions);
+            }
+        }
     }
 }
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.morph = None;
+    model.point_buffer = build_point_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -99,15 +108,15 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
             match key {
                 Key::Right => {
                     if model.iteration < 7 {
+                        model.morph = Some((build_morph(model.iteration, model.window_dimensions), 0));
                         model.iteration += 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration,INITIAL_LINE_LENGTH)
                 },
                 Key::Left => {
                     if model.iteration > 0 {
                         model.iteration -= 1;
+                        model.morph = Some((build_morph(model.iteration, model.window_dimensions).reversed(), 0));
                     }
-                    model.point_buffer = build_point_buffer(model.iteration,INITIAL_LINE_LENGTH)
                 }
                 Key::Up => {
                     model.thickness += 1.0;
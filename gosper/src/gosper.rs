@@ -1,10 +1,11 @@
-use common::l_system::Grammar;
+use common::l_system::{Alphabet, Grammar};
+use common::l_system::turtle::Command;
 use nannou::geom::Point2;
 
 /// https://wikivisually.com/wiki/Gosper_curve
 ///
 /// Alphabet for the grammar.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Gosper {
     A,
     B,
@@ -26,30 +27,64 @@ impl Grammar for Gosper {
         }
     }
 
-    fn reify(v: &Self, current_pt: &mut Self::Item, current_angle: &mut f32, angle_step: f32, line_length: f32) -> Option<Self::Item>
-    {
+    fn command(&self) -> Option<Command> {
         use Gosper::*;
-        let cpy = current_pt.clone();
-        match v {
-            A => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            B => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            Plus => *current_angle += angle_step,
-            Minus => *current_angle -= angle_step,
-        };
-
-        match v {
-            A | B => Some(cpy),
-            Plus | Minus => None
+        match self {
+            A | B => Some(Command::Draw),
+            Plus => Some(Command::TurnLeft),
+            Minus => Some(Command::TurnRight)
         }
     }
 }
 
 
-/// Produces a new point from the current one
-#[inline]
-fn line_to(pt: Point2, angle: f32, line_length: f32) -> Point2 {
-    Point2 {
-        x: pt.x + line_length * angle.cos(),
-        y: pt.y + line_length * angle.sin()
+impl Alphabet for Gosper {
+    fn alphabet() -> Vec<Self> {
+        use Gosper::*;
+        vec![A, B, Plus, Minus]
+    }
+
+    fn to_char(&self) -> char {
+        use Gosper::*;
+        match self {
+            // Fractint only draws with F and D.
+            A => 'F',
+            B => 'D',
+            Plus => '+',
+            Minus => '-'
+        }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn fractint_export() {
+    use common::l_system::dynamic::DynamicGrammar;
+    use common::l_system::fractint;
+    use Gosper::*;
+    let grammar = DynamicGrammar::from_alphabet(&[A], std::f32::consts::FRAC_PI_3);
+    assert_eq!(fractint::write("Gosper", &grammar).unwrap(), "Gosper {\n  Angle 6\n  Axiom F\n  D=+F-DD--D-F++F+D\n  F=F-D--D+F++FF+D-\n}\n");
+}
+
+#[test]
+fn points_are_found_without_expanding() {
+    use common::l_system::LSystem;
+    use std::f32::consts::FRAC_PI_3;
+    let origin = Point2 { x: 0.0, y: 0.0 };
+    let lsystem = LSystem::new(vec![Gosper::A]);
+
+    let access = lsystem.random_access(4).unwrap();
+    let turtle = access.turtle(FRAC_PI_3, 1.0);
+    let points: Vec<Point2> = lsystem.clone().iterate_n(4).reify_iter(FRAC_PI_3, 1.0, origin).collect();
+    [0, 1, 100, 1234, 2400].iter().for_each(|k| {
+        let pt = turtle.point(*k as u64, origin).unwrap();
+        assert!((pt.x - points[*k].x).abs() < 0.001 && (pt.y - points[*k].y).abs() < 0.001);
+    });
+
+    // Each iteration scales the distance between the ends by √7,
+    // and turns it by the angle of the first iteration's end point, (5/2, -√3/2).
+    let access = lsystem.random_access(10).unwrap();
+    let (end, _) = access.turtle(FRAC_PI_3, 1.0).state(access.len(), origin).unwrap();
+    let turn = -(3.0f32.sqrt() / 5.0).atan() * 10.0;
+    let expected = Point2 { x: 7.0f32.powi(5) * turn.cos(), y: 7.0f32.powi(5) * turn.sin() };
+    assert!((end.x - expected.x).abs() < 0.1 && (end.y - expected.y).abs() < 0.1, "{:?}", end);
+}
@@ -1,3 +1,3 @@
 
-mod gosper;
+pub mod gosper;
 pub mod day_9;
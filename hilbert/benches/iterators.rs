@@ -0,0 +1,98 @@
+//! Compares the iterators against the way they used to work, calling `d2xy` from scratch for every point.
+//!
+//! Run with `cargo bench -p hilbert`.
+
+use hilbert::{d2xy, HilbertIterator, Point, RegularHilbertIterator};
+use std::time::{Duration, Instant};
+
+const ITERATION: usize = 12;
+const RUNS: u32 = 5;
+
+/// `RegularHilbertIterator` as it was, which never ends.
+struct OldRegularHilbertIterator {
+    iteration: usize,
+    d: usize
+}
+
+impl Iterator for OldRegularHilbertIterator {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = 1 << self.iteration;
+        let ret = Some(d2xy(n, self.d));
+        self.d += 1;
+        ret
+    }
+}
+
+/// `HilbertIterator` as it was, which finds both ends of every line.
+struct OldHilbertIterator {
+    iteration: usize,
+    d: usize
+}
+
+impl Iterator for OldHilbertIterator {
+    type Item = (Point, Point);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = 1 << self.iteration;
+        let d_max = n * n - 1;
+
+        let pt_0 = d2xy(n, self.d);
+        self.d += 1;
+        let pt_1 = d2xy(n, self.d);
+
+        if self.d > d_max {
+            None
+        } else {
+            Some((pt_0, pt_1))
+        }
+    }
+}
+
+/// The average time taken to run the function, along with the number of items it produced.
+fn time<F: Fn() -> usize>(f: F) -> (Duration, usize) {
+    let mut len = 0;
+    let start = Instant::now();
+    (0..RUNS).for_each(|_| len = f());
+    (start.elapsed() / RUNS, len)
+}
+
+fn report(name: &str, (duration, len): (Duration, usize)) {
+    println!("{:<36} {:>10.2?} ({} items)", name, duration, len);
+}
+
+fn main() {
+    let d_max = RegularHilbertIterator::new_with_iteration(ITERATION).d_max();
+    println!("iteration {}", ITERATION);
+
+    // The sketches collect the curve into a buffer, so that is what's timed.
+    report("old RegularHilbertIterator", time(|| {
+        OldRegularHilbertIterator { iteration: ITERATION, d: 0 }
+            .take(d_max + 1)
+            .collect::<Vec<Point>>()
+            .len()
+    }));
+    report("RegularHilbertIterator", time(|| {
+        RegularHilbertIterator::new_with_iteration(ITERATION)
+            .collect::<Vec<Point>>()
+            .len()
+    }));
+    report("RegularHilbertIterator, reversed", time(|| {
+        RegularHilbertIterator::new_with_iteration(ITERATION)
+            .rev()
+            .collect::<Vec<Point>>()
+            .len()
+    }));
+
+    report("old HilbertIterator", time(|| {
+        OldHilbertIterator { iteration: ITERATION, d: 0 }
+            .collect::<Vec<(Point, Point)>>()
+            .len()
+    }));
+    report("HilbertIterator", time(|| {
+        HilbertIterator::new_with_iteration(ITERATION)
+            .collect::<Vec<(Point, Point)>>()
+            .len()
+    }));
+}
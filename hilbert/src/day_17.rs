@@ -2,6 +2,7 @@ use nannou::prelude::*;
 use std::num::Wrapping;
 use crate::hilbert::{RegularHilbertIterator};
 use common::collinear::condense_collinear;
+use common::fit::BoundingBox;
 
 pub struct Model {
     _window: WindowId,
@@ -13,20 +14,19 @@ pub struct Model {
     line_buffer: Vec<(Point2)>
 }
 
-fn fill_line_buffer(iteration: usize) -> Vec<Point2> {
-    const SCALE: f32 = 480.0;
-    fn transform(i: f32, n: f32) -> f32 {
-        ((i - ((n - 1.0) / 2.0)) / n) * SCALE
-    }
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 16.0;
+
+fn fill_line_buffer(iteration: usize, window_dimensions: Vector2) -> Vec<Point2> {
     let n = RegularHilbertIterator::new_with_iteration(iteration).n();
-    let max_d = RegularHilbertIterator::new_with_iteration(iteration).d_max();
+    // The curve visits every cell of an n by n grid.
+    let fit = BoundingBox {
+        min: Point2 { x: 0.0, y: 0.0 },
+        max: Point2 { x: (n - 1) as f32, y: (n - 1) as f32 },
+    }.fit(window_dimensions, MARGIN);
 
     let point_buffer: Vec<Point2> = RegularHilbertIterator::new_with_iteration(iteration)
-        .take(max_d + 1)
-        .map(|pt| Point2 {
-            x: transform(pt.x() as f32, n as f32),
-            y: transform(pt.y() as f32, n as f32)
-        })
+        .map(|pt| fit.apply(Point2 { x: pt.x() as f32, y: pt.y() as f32 }))
         .collect();
     // Saves about a fifth of the size.
     let point_buffer:Vec<Point2> = condense_collinear(point_buffer);
@@ -38,7 +38,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title("day 6")
             .view(view) // The function that will be called for presenting graphics to a frame.
             .event(event) // The function that will be called when the window receives events.
@@ -46,12 +46,14 @@ impl Model {
             .build()
             .unwrap();
 
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             iteration: 0,
-            line_buffer: fill_line_buffer(0)
+            line_buffer: fill_line_buffer(0, window_dimensions)
         }
     }
 
@@ -66,7 +68,7 @@ impl Model {
         if model.frame_counter.0 > (max_d / speed) + 45 {
             model.frame_counter.0 = 0;
             model.iteration += 1;
-            model.line_buffer = fill_line_buffer(model.iteration);
+            model.line_buffer = fill_line_buffer(model.iteration, model.window_dimensions);
         }
 
     }
@@ -74,6 +76,7 @@ impl Model {
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.line_buffer = fill_line_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -86,7 +89,7 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
         WindowEvent::KeyPressed(Key::Space) => {
             model.frame_counter = Wrapping(0);
             model.iteration = 0;
-            model.line_buffer = fill_line_buffer(model.iteration);
+            model.line_buffer = fill_line_buffer(model.iteration, model.window_dimensions);
         }
         WindowEvent::KeyPressed(Key::Q) => {
             std::process::exit(0); // Q -> exit program
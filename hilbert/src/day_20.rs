@@ -0,0 +1,139 @@
+//! A 3D Hilbert curve, turning so that its depth can be seen.
+
+use nannou::prelude::*;
+use std::num::Wrapping;
+use crate::hilbert_nd::HilbertIterator3d;
+use common::draw::{colored_lines_no_corners, rainbow};
+
+pub struct Model {
+    _window: WindowId,
+    window_dimensions: Vector2,
+    frame_counter: Wrapping<usize>,
+    /// The iteration to draw.
+    iteration: usize,
+    /// The cells of the curve, centered on the origin and scaled so the cube fits the window however it's turned.
+    cells: Vec<Vector3>,
+    thickness: f32,
+}
+
+
+const ITERATION: usize = 3;
+const MAX_ITERATION: usize = 5;
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 20.0;
+/// How far the cube turns each frame, in radians.
+const TURN: f32 = 0.01;
+/// How far the cube is tipped towards the viewer, so its top can be seen.
+const TILT: f32 = 0.5;
+const INITIAL_THICKNESS: f32 = 2.0;
+const THICKNESS_STEP: f32 = 0.5;
+
+
+fn build_cells(iteration: usize, window_dimensions: Vector2) -> Vec<Vector3> {
+    let iter = HilbertIterator3d::new_with_iteration(iteration);
+    let half_side = (iter.n() - 1) as f32 / 2.0;
+    // The diagonal of the cube is the widest it gets when turned.
+    let available = window_dimensions.x.min(window_dimensions.y) - 2.0 * MARGIN;
+    let scale = if half_side > 0.0 { available / (2.0 * half_side * 3.0f32.sqrt()) } else { 1.0 };
+
+    iter
+        .map(|[x, y, z]| vec3(x as f32 - half_side, y as f32 - half_side, z as f32 - half_side) * scale)
+        .collect()
+}
+
+/// Turns the cells about the vertical axis, tips them towards the viewer, and flattens them onto the window.
+fn project(cells: &[Vector3], angle: f32) -> Vec<Point2> {
+    let (sin, cos) = angle.sin_cos();
+    let (tilt_sin, tilt_cos) = TILT.sin_cos();
+    cells
+        .iter()
+        .map(|cell| {
+            let x = cell.x * cos + cell.z * sin;
+            let z = cell.z * cos - cell.x * sin;
+            let y = cell.y * tilt_cos - z * tilt_sin;
+            pt2(x, y)
+        })
+        .collect()
+}
+
+
+impl Model {
+    pub fn init(app: &App) -> Model {
+        let _window = app
+            .new_window()
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
+            .with_title("day 20")
+            .view(view) // The function that will be called for presenting graphics to a frame.
+            .event(event) // The function that will be called when the window receives events.
+            .resized(on_resize)
+            .build()
+            .unwrap();
+
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+
+        Model {
+            _window,
+            window_dimensions,
+            frame_counter: Wrapping(0),
+            iteration: ITERATION,
+            cells: build_cells(ITERATION, window_dimensions),
+            thickness: INITIAL_THICKNESS,
+        }
+    }
+
+    pub fn update(_app: &App, model: &mut Model, _update: Update) {
+        model.frame_counter += Wrapping(1);
+    }
+}
+
+fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
+    model.window_dimensions = dimensions;
+    model.cells = build_cells(model.iteration, dimensions);
+}
+
+/// Handle events related to the window and update the model if necessary
+fn event(_app: &App, model: &mut Model, event: WindowEvent) {
+    match event {
+        WindowEvent::KeyPressed(key) => {
+            match key {
+                Key::Right => {
+                    if model.iteration < MAX_ITERATION {
+                        model.iteration += 1;
+                    }
+                    model.cells = build_cells(model.iteration, model.window_dimensions);
+                },
+                Key::Left => {
+                    if model.iteration > 1 {
+                        model.iteration -= 1;
+                    }
+                    model.cells = build_cells(model.iteration, model.window_dimensions);
+                }
+                Key::Up => {
+                    model.thickness += THICKNESS_STEP;
+                }
+                Key::Down => {
+                    model.thickness -= THICKNESS_STEP;
+                }
+                Key::Q => {
+                    std::process::exit(0); // Q -> exit program
+                }
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) -> Frame {
+    let draw = app.draw();
+
+    frame.clear(DARK_CHARCOAL);
+
+    let points = project(&model.cells, model.frame_counter.0 as f32 * TURN);
+    colored_lines_no_corners(&draw, &points, model.thickness, 0, rainbow());
+
+    // Write to the window frame.
+    draw.to_frame(app, &frame).unwrap();
+    // Return the drawn frame.
+    frame
+}
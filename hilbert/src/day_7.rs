@@ -1,67 +1,93 @@
 use nannou::prelude::*;
 use std::num::Wrapping;
-use crate::hilbert::{HilbertIterator, Point};
+use crate::hilbert::Point;
+use crate::gilbert::Gilbert;
+use crate::space_filling::{curves, CurveIterator, SpaceFillingCurve};
+use common::fit::{BoundingBox, Fit};
 
 pub struct Model {
     _window: WindowId,
     window_dimensions: Vector2,
     frame_counter: Wrapping<usize>,
     /// Buffer containing all of the lines needed to draw the complete curve for the current iteration.
-    line_buffer: Vec<(Point2, Point2)>
+    line_buffer: Vec<(Point2, Point2)>,
+    /// How far along the curve the cell under the mouse is.
+    hovered: Option<usize>,
+    /// Every curve that can be drawn, and which of them is being drawn.
+    curves: Vec<Box<dyn SpaceFillingCurve>>,
+    curve: usize
 }
 
 
 const ITERATION: usize = 5;
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 25.0;
+
+fn grid_fit(curve: &dyn SpaceFillingCurve, window_dimensions: Vector2) -> Fit {
+    // The curve visits every cell of a width by height grid.
+    BoundingBox {
+        min: Point2 { x: 0.0, y: 0.0 },
+        max: Point2 { x: (curve.width() - 1) as f32, y: (curve.height() - 1) as f32 },
+    }.fit(window_dimensions, MARGIN)
+}
 
-fn fill_line_buffer(iteration: usize, window_dimensions: Vector2) -> Vec<(Point2, Point2)> {
-    fn transform(i: f32, n: f32, min_dimension: f32) -> f32 {
-        ((i - ((n - 1.0) / 2.0)) / n) * min_dimension
-    }
-    let n = HilbertIterator::new_with_iteration(iteration).n();
+/// The square curves, followed by a generalized Hilbert curve with cells of the same size that fills the whole window.
+fn all_curves(window_dimensions: Vector2) -> Vec<Box<dyn SpaceFillingCurve>> {
+    let mut curves = curves(ITERATION);
+    let n = curves[0].side_len();
+    let available = window_dimensions - vec2(2.0 * MARGIN, 2.0 * MARGIN);
+    let cell = available.x.min(available.y) / n as f32;
+    let width = ((available.x / cell).round() as usize).max(1);
+    let height = ((available.y / cell).round() as usize).max(1);
+    curves.push(Box::new(Gilbert::new(width, height)));
+    curves
+}
 
-    let scale = {
-        let s: f32 = if window_dimensions.x < window_dimensions.y {
-            window_dimensions.x
-        } else {
-            window_dimensions.y
-        };
-        s - 50.0
-    };
+fn fill_line_buffer(curve: &dyn SpaceFillingCurve, window_dimensions: Vector2) -> Vec<(Point2, Point2)> {
+    let fit = grid_fit(curve, window_dimensions);
+    let place = |pt: Point| fit.apply(Point2 { x: pt.x() as f32, y: pt.y() as f32 });
 
-    HilbertIterator::new_with_iteration(iteration)
-        .map(|(pt_0, pt_1): (Point, Point)| {
-            (
-                Point2 {
-                    x: transform(pt_0.x() as f32, n as f32, scale),
-                    y: transform(pt_0.y() as f32, n as f32, scale),
-                },
-                Point2 {
-                    x: transform(pt_1.x() as f32, n as f32, scale),
-                    y: transform(pt_1.y() as f32, n as f32, scale),
-                },
-            )
-        })
+    let mut points: Vec<Point2> = CurveIterator::new(curve).map(place).collect();
+    if curve.is_closed() {
+        points.push(points[0]);
+    }
+    points
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
         .collect()
 }
 
+fn title(curve: &dyn SpaceFillingCurve, hovered: Option<usize>) -> String {
+    match hovered {
+        Some(d) => format!("day 7 - {} - d = {}", curve.name(), d),
+        None => format!("day 7 - {}", curve.name())
+    }
+}
+
 
 impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
-            .with_title("day 7")
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
+            .with_title("day 7 - Hilbert")
             .view(view) // The function that will be called for presenting graphics to a frame.
             .event(event) // The function that will be called when the window receives events.
             .resized(on_resize)
             .build()
             .unwrap();
 
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let curves = all_curves(window_dimensions);
+
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
-            line_buffer: fill_line_buffer(ITERATION, Vector2::default())
+            line_buffer: fill_line_buffer(curves[0].as_ref(), window_dimensions),
+            hovered: None,
+            curves,
+            curve: 0
         }
     }
 
@@ -72,16 +98,41 @@ impl Model {
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
-    model.line_buffer = fill_line_buffer(ITERATION, model.window_dimensions);
+    model.curves = all_curves(dimensions);
+    model.hovered = None;
+    model.line_buffer = fill_line_buffer(model.curves[model.curve].as_ref(), model.window_dimensions);
+}
+
+/// Finds how far along the curve the cell under the mouse is, and shows it in the title.
+fn hover(app: &App, model: &mut Model, mouse: Point2) {
+    let curve = model.curves[model.curve].as_ref();
+    let cell = grid_fit(curve, model.window_dimensions).invert(mouse);
+    let (x, y) = (cell.x.round(), cell.y.round());
+    model.hovered = if x >= 0.0 && y >= 0.0 && x < curve.width() as f32 && y < curve.height() as f32 {
+        Some(curve.point_to_index(Point::new(x as usize, y as usize)))
+    } else {
+        None
+    };
+
+    if let Some(window) = app.window(model._window) {
+        window.set_title(&title(curve, model.hovered));
+    }
 }
 
 /// Handle events related to the window and update the model if necessary
-fn event(_app: &App, model: &mut Model, event: WindowEvent) {
+fn event(app: &App, model: &mut Model, event: WindowEvent) {
     match event {
-        WindowEvent::MouseMoved(_point) => {
+        WindowEvent::MouseMoved(point) => {
+            hover(app, model, point);
         }
         WindowEvent::MousePressed(_) => {
         }
+        WindowEvent::KeyPressed(Key::Tab) => {
+            // Switch to the next curve, keeping whatever is under the mouse highlighted so the orders can be compared.
+            model.curve = (model.curve + 1) % model.curves.len();
+            model.line_buffer = fill_line_buffer(model.curves[model.curve].as_ref(), model.window_dimensions);
+            hover(app, model, pt2(app.mouse.x, app.mouse.y));
+        }
         WindowEvent::KeyPressed(Key::Space) => {
             model.frame_counter = Wrapping(0);
         }
@@ -127,6 +178,17 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
                 .radius(HALF_THICKNESS);
         });
 
+    if let Some(d) = model.hovered {
+        let cell = match model.line_buffer.get(d) {
+            Some((pt, _)) => *pt,
+            None => model.line_buffer[len - 1].1
+        };
+        draw.ellipse()
+            .xy(cell)
+            .color(WHITE)
+            .radius(THICKNESS);
+    }
+
 
     // Write to the window frame.
     draw.to_frame(app, &frame).unwrap();
@@ -1,6 +1,7 @@
 use nannou::prelude::*;
 use std::num::Wrapping;
 use crate::hilbert::{HilbertIterator, Point};
+use common::fit::BoundingBox;
 
 pub struct Model {
     _window: WindowId,
@@ -15,27 +16,20 @@ pub struct Model {
 
 const ITERATION: usize = 4;
 const INITIAL_THICKNESS: f32 = 10.0;
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 16.0;
 
-fn fill_line_buffer(iteration: usize) -> Vec<(Point2, Point2)> {
-    const SCALE: f32 = 480.0;
-    fn transform(i: f32, n: f32) -> f32 {
-        ((i - ((n - 1.0) / 2.0)) / n) * SCALE
-    }
+fn fill_line_buffer(iteration: usize, window_dimensions: Vector2) -> Vec<(Point2, Point2)> {
     let n = HilbertIterator::new_with_iteration(iteration).n();
+    // The curve visits every cell of an n by n grid.
+    let fit = BoundingBox {
+        min: Point2 { x: 0.0, y: 0.0 },
+        max: Point2 { x: (n - 1) as f32, y: (n - 1) as f32 },
+    }.fit(window_dimensions, MARGIN);
+    let place = |pt: Point| fit.apply(Point2 { x: pt.x() as f32, y: pt.y() as f32 });
 
     HilbertIterator::new_with_iteration(iteration)
-        .map(|(pt_0, pt_1): (Point, Point)| {
-            (
-                Point2 {
-                    x: transform(pt_0.x() as f32, n as f32),
-                    y: transform(pt_0.y() as f32, n as f32),
-                },
-                Point2 {
-                    x: transform(pt_1.x() as f32, n as f32),
-                    y: transform(pt_1.y() as f32, n as f32),
-                },
-            )
-        })
+        .map(|(pt_0, pt_1): (Point, Point)| (place(pt_0), place(pt_1)))
         .collect()
 }
 
@@ -44,7 +38,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title("day 8")
             .view(view) // The function that will be called for presenting graphics to a frame.
             .event(event) // The function that will be called when the window receives events.
@@ -52,11 +46,13 @@ impl Model {
             .build()
             .unwrap();
 
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
-            line_buffer: fill_line_buffer(ITERATION),
+            line_buffer: fill_line_buffer(ITERATION, window_dimensions),
             iteration: ITERATION,
             thickness: INITIAL_THICKNESS
         }
@@ -69,6 +65,7 @@ impl Model {
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.line_buffer = fill_line_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -82,11 +79,11 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
             match key {
                 Key::Right => {
                     model.iteration += 1;
-                    model.line_buffer = fill_line_buffer(model.iteration);
+                    model.line_buffer = fill_line_buffer(model.iteration, model.window_dimensions);
                 },
                 Key::Left => {
                     model.iteration -= 1;
-                    model.line_buffer = fill_line_buffer(model.iteration);
+                    model.line_buffer = fill_line_buffer(model.iteration, model.window_dimensions);
                 }
                 Key::Up => {
                     model.thickness += 1.0;
@@ -0,0 +1,193 @@
+//! The generalized Hilbert curve, or "gilbert", which fills a grid of any width and height,
+//! following Jakub Červený's construction (https://github.com/jakubcerveny/gilbert).
+//!
+//! The grid is split in two along its longer side when it's much longer than it is wide,
+//! and otherwise into the three parts of a Hilbert curve's U shape, choosing where to split
+//! so that each part has an even length wherever that's possible.
+//! Each part is filled the same way, down to single rows that are walked straight along.
+//!
+//! Consecutive cells share an edge, except when the longer side is odd and the shorter side is even:
+//! then the curve can't end at the other corner of the longer side without one diagonal step.
+
+use crate::hilbert::Point;
+use crate::space_filling::SpaceFillingCurve;
+
+/// Every cell of a width by height grid, in the order that the curve visits them.
+///
+/// The curve starts at the origin and ends at the other end of the longer side.
+pub fn gilbert(width: usize, height: usize) -> Vec<Point> {
+    let mut points = Vec::with_capacity(width * height);
+    if width == 0 || height == 0 {
+        return points
+    }
+    let (width, height) = (width as i64, height as i64);
+    if width >= height {
+        generate(&mut points, (0, 0), (width, 0), (0, height));
+    } else {
+        generate(&mut points, (0, 0), (0, height), (width, 0));
+    }
+    points
+}
+
+/// Fills the rectangle with a corner at `origin`, spanned by `a` along its major direction and `b` across it.
+///
+/// The curve starts at the origin and ends at the far end of `a`.
+fn generate(points: &mut Vec<Point>, origin: (i64, i64), a: (i64, i64), b: (i64, i64)) {
+    let (x, y) = origin;
+    let (ax, ay) = a;
+    let (bx, by) = b;
+    let w = (ax + ay).abs();
+    let h = (bx + by).abs();
+    // Unit steps along and across.
+    let (dax, day) = (ax.signum(), ay.signum());
+    let (dbx, dby) = (bx.signum(), by.signum());
+
+    if h == 1 {
+        (0..w).for_each(|i| points.push(Point::new((x + i * dax) as usize, (y + i * day) as usize)));
+        return
+    }
+    if w == 1 {
+        (0..h).for_each(|i| points.push(Point::new((x + i * dbx) as usize, (y + i * dby) as usize)));
+        return
+    }
+
+    // Halving rounds towards negative infinity, so that halves of negative spans line up with positive ones.
+    let (mut ax2, mut ay2) = (ax >> 1, ay >> 1);
+    let (mut bx2, mut by2) = (bx >> 1, by >> 1);
+    let w2 = (ax2 + ay2).abs();
+    let h2 = (bx2 + by2).abs();
+
+    if 2 * w > 3 * h {
+        // Long and thin, so split it into two along its length.
+        if w2 % 2 != 0 && w > 2 {
+            ax2 += dax;
+            ay2 += day;
+        }
+        generate(points, (x, y), (ax2, ay2), (bx, by));
+        generate(points, (x + ax2, y + ay2), (ax - ax2, ay - ay2), (bx, by));
+    } else {
+        // Up across the first half, along the far side, and back down across the second half.
+        if h2 % 2 != 0 && h > 2 {
+            bx2 += dbx;
+            by2 += dby;
+        }
+        generate(points, (x, y), (bx2, by2), (ax2, ay2));
+        generate(points, (x + bx2, y + by2), (ax, ay), (bx - bx2, by - by2));
+        generate(
+            points,
+            (x + (ax - dax) + (bx2 - dbx), y + (ay - day) + (by2 - dby)),
+            (-bx2, -by2),
+            (-(ax - ax2), -(ay - ay2))
+        );
+    }
+}
+
+
+/// The generalized Hilbert curve through a grid of a particular size.
+///
+/// There's no quick way to find a cell from scratch, so the whole curve is found up front.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gilbert {
+    width: usize,
+    height: usize,
+    points: Vec<Point>,
+    /// How far along the curve each cell is, row by row.
+    indices: Vec<usize>
+}
+
+impl Gilbert {
+    pub fn new(width: usize, height: usize) -> Self {
+        let points = gilbert(width, height);
+        let mut indices = vec![0; width * height];
+        points.iter().enumerate().for_each(|(d, pt)| indices[pt.y() * width + pt.x()] = d);
+        Gilbert {
+            width,
+            height,
+            points,
+            indices
+        }
+    }
+}
+
+impl SpaceFillingCurve for Gilbert {
+    fn name(&self) -> &'static str {
+        "Gilbert"
+    }
+
+    fn side_len(&self) -> usize {
+        self.width.max(self.height)
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index_to_point(&self, d: usize) -> Point {
+        self.points[d]
+    }
+
+    fn point_to_index(&self, pt: Point) -> usize {
+        self.indices[pt.y() * self.width + pt.x()]
+    }
+}
+
+
+#[test]
+fn fills_any_rectangle() {
+    (1..=24).for_each(|width| (1..=24).for_each(|height| {
+        let points = gilbert(width, height);
+        assert_eq!(points.len(), width * height);
+        let mut seen = vec![false; width * height];
+        points.iter().for_each(|pt| {
+            assert!(pt.x() < width && pt.y() < height, "{:?} is outside {}x{}", pt, width, height);
+            assert!(!seen[pt.y() * width + pt.x()], "{:?} was visited twice in {}x{}", pt, width, height);
+            seen[pt.y() * width + pt.x()] = true;
+        });
+    }));
+}
+
+#[test]
+fn steps_between_neighbours() {
+    (1..=24).for_each(|width| (1..=24).for_each(|height| {
+        let points = gilbert(width, height);
+        let diagonals = points
+            .windows(2)
+            .filter(|pair| {
+                let dx = (pair[0].x() as i64 - pair[1].x() as i64).abs();
+                let dy = (pair[0].y() as i64 - pair[1].y() as i64).abs();
+                assert!(dx <= 1 && dy <= 1 && dx + dy > 0, "{:?} jumps in {}x{}", pair, width, height);
+                dx + dy == 2
+            })
+            .count();
+        let (long, short) = if width >= height { (width, height) } else { (height, width) };
+        let expected = if long % 2 == 1 && short % 2 == 0 { 1 } else { 0 };
+        assert!(diagonals <= expected, "{} diagonal steps in {}x{}", diagonals, width, height);
+    }));
+}
+
+#[test]
+fn ends_along_the_longer_side() {
+    assert_eq!(gilbert(7, 3).first(), Some(&Point::new(0, 0)));
+    assert_eq!(gilbert(7, 3).last(), Some(&Point::new(6, 0)));
+    assert_eq!(gilbert(3, 7).last(), Some(&Point::new(0, 6)));
+    assert!(gilbert(0, 5).is_empty());
+}
+
+#[test]
+fn round_trip() {
+    let curve = Gilbert::new(13, 8);
+    (0..13 * 8).for_each(|d| assert_eq!(curve.point_to_index(curve.index_to_point(d)), d));
+}
+
+#[test]
+fn is_the_hilbert_curve_on_powers_of_two() {
+    (0..=6).for_each(|iteration| {
+        let n = 1 << iteration;
+        let hilbert: Vec<Point> = (0..n * n).map(|d| crate::hilbert::d2xy(n, d)).collect();
+        assert_eq!(gilbert(n, n), hilbert);
+    });
+}
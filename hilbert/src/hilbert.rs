@@ -6,6 +6,13 @@ pub struct Point {
 }
 
 impl Point {
+    pub fn new(x: usize, y: usize) -> Self {
+        Point {
+            x,
+            y
+        }
+    }
+
     pub fn x(&self) -> usize {
         self.x
     }
@@ -35,6 +42,23 @@ pub fn d2xy(n: usize, d: usize) -> Point {
 
 }
 
+/// Finds how far along the curve a cell of the n by n grid is, undoing `d2xy`.
+pub fn xy2d(n: usize, pt: Point) -> usize {
+    let mut pt = pt;
+    let mut d = 0;
+    let mut s = n / 2;
+
+    while s > 0 {
+        let rx = if pt.x & s > 0 { 1 } else { 0 };
+        let ry = if pt.y & s > 0 { 1 } else { 0 };
+        d += s * s * ((3 * rx) ^ ry);
+        // Flipping every bit of a coordinate leaves the lower bits where the smaller quadrants expect them.
+        pt = rot(n, pt, rx, ry);
+        s /= 2;
+    }
+    d
+}
+
 
 fn rot(n: usize, mut pt: Point, rx: usize, ry: usize) -> Point {
     if ry == 0 {
@@ -72,6 +96,32 @@ fn hilbert_n_2() {
     });
 }
 
+#[test]
+fn round_trip() {
+    (0..=10).for_each(|iteration| {
+        let n = 1 << iteration;
+        (0..n * n).for_each(|d| {
+            assert_eq!(xy2d(n, d2xy(n, d)), d);
+        });
+    });
+}
+
+#[test]
+fn walks_backwards() {
+    let forwards: Vec<(Point, Point)> = HilbertIterator::new_with_iteration(3).collect();
+    let mut backwards: Vec<(Point, Point)> = HilbertIterator::new_with_iteration(3).rev().collect();
+    backwards.reverse();
+    assert_eq!(forwards, backwards);
+
+    let mut iter = HilbertIterator::new_with_iteration(2);
+    assert_eq!(iter.len(), 15);
+    assert_eq!(iter.next(), Some((d2xy(4, 0), d2xy(4, 1))));
+    assert_eq!(iter.next_back(), Some((d2xy(4, 14), d2xy(4, 15))));
+    assert_eq!(iter.len(), 13);
+    assert_eq!(iter.by_ref().count(), 13);
+    assert_eq!(iter.next_back(), None);
+}
+
 #[test]
 fn iteration_limit_2() {
     let iter = HilbertIterator::new_with_iteration(2);
@@ -84,9 +134,154 @@ fn iteration_limit_3() {
 }
 
 
+/// The orientation of a quadrant, as a reflection of the grid.
+///
+/// The reflections that the curve uses combine like bits being toggled:
+/// the first bit swaps x and y, the second negates both.
+/// As in `rot`, the quadrants picked by digits of 0 and 3 are swapped, and those picked by a 3 are negated as well.
+type Orientation = u8;
+
+/// The step from the end of the curve through a quadrant to the start of the curve through the next one,
+/// for each orientation and the digit of the first quadrant.
+///
+/// Unoriented, the curve steps up, right and then down between quadrants.
+/// This is a table rather than a calculation because the orientation changes unpredictably from step to step,
+/// which makes branching on it slow.
+const QUADRANT_STEPS: [[(isize, isize); 3]; 4] = [
+    [(0, 1), (1, 0), (0, -1)],
+    // Swapped
+    [(1, 0), (0, 1), (-1, 0)],
+    // Negated
+    [(0, -1), (-1, 0), (0, 1)],
+    // Swapped and negated
+    [(-1, 0), (0, -1), (1, 0)],
+];
+
+/// The low bit of every digit of `d`.
+const LOW_BITS: usize = !0 / 3;
+
+/// A point on the curve that can be moved along it one step at a time.
+///
+/// Consecutive cells of a quadrant's curve are always one step apart,
+/// and so are the end of one quadrant and the start of the next.
+/// So moving along the curve only needs the direction of that step, which depends on the orientation
+/// of the smallest quadrant holding both cells.
+/// That orientation combines the orientations picked by every digit of `d` above it,
+/// and since they combine like toggling bits, it can be read off the digits by counting them,
+/// so each step takes the same time rather than visiting every level as `d2xy` does.
+#[derive(Clone, Debug)]
+struct Cursor {
+    iteration: usize,
+    d: usize,
+    x: usize,
+    y: usize
+}
+
+impl Cursor {
+    fn new(iteration: usize, d: usize) -> Self {
+        let pt = d2xy(1 << iteration, d);
+        Cursor {
+            iteration,
+            d,
+            x: pt.x,
+            y: pt.y
+        }
+    }
+
+    fn digit(&self, level: usize) -> usize {
+        (self.d >> (2 * level)) & 3
+    }
+
+    /// The orientation given to a level of quadrants by the levels above it.
+    fn orientation_above(&self, level: usize) -> Orientation {
+        let levels = self.iteration - level - 1;
+        let digits = self.d >> (2 * (level + 1));
+        // Digits with unequal bits are a 1 or a 2.
+        let unequal = ((digits >> 1) ^ digits) & LOW_BITS;
+        let threes = (digits >> 1) & digits & LOW_BITS;
+        let swaps = levels - unequal.count_ones() as usize;
+        let negations = threes.count_ones() as usize;
+        ((swaps & 1) | ((negations & 1) << 1)) as Orientation
+    }
+
+    fn point(&self) -> Point {
+        Point {
+            x: self.x,
+            y: self.y
+        }
+    }
+
+    fn move_by(&mut self, (x, y): (isize, isize)) {
+        self.x = (self.x as isize + x) as usize;
+        self.y = (self.y as isize + y) as usize;
+    }
+
+    /// Moves to the next point. Must not be called at the end of the curve.
+    fn step(&mut self) {
+        // The lowest digit that isn't a 3 is the one that increases, and the ones below it carry.
+        let level = ((!self.d).trailing_zeros() / 2) as usize;
+        let step = QUADRANT_STEPS[self.orientation_above(level) as usize][self.digit(level)];
+        self.move_by(step);
+        self.d += 1;
+    }
+
+    /// Moves to the previous point. Must not be called at the start of the curve.
+    fn step_back(&mut self) {
+        // The lowest digit that isn't a 0 is the one that decreases, and the ones below it borrow.
+        let level = (self.d.trailing_zeros() / 2) as usize;
+        let (x, y) = QUADRANT_STEPS[self.orientation_above(level) as usize][self.digit(level) - 1];
+        self.move_by((-x, -y));
+        self.d -= 1;
+    }
+}
+
+#[test]
+fn cursor_matches_d2xy() {
+    (0..=6).for_each(|iteration| {
+        let n = 1 << iteration;
+        let d_max = n * n - 1;
+        let mut cursor = Cursor::new(iteration, 0);
+        (0..=d_max).for_each(|d| {
+            assert_eq!(cursor.point(), d2xy(n, d));
+            if d < d_max {
+                cursor.step();
+            }
+        });
+        (0..=d_max).rev().for_each(|d| {
+            assert_eq!(cursor.point(), d2xy(n, d));
+            if d > 0 {
+                cursor.step_back();
+            }
+        });
+        assert_eq!(Cursor::new(iteration, d_max / 2).point(), d2xy(n, d_max / 2));
+    });
+}
+
+#[test]
+fn iterators_end() {
+    assert_eq!(RegularHilbertIterator::new_with_iteration(0).collect::<Vec<Point>>(), vec![Point::default()]);
+    assert_eq!(HilbertIterator::new_with_iteration(0).count(), 0);
+
+    let mut iter = RegularHilbertIterator::new_with_iteration(4);
+    assert_eq!(iter.len(), 256);
+    let points: Vec<Point> = iter.by_ref().collect();
+    assert_eq!(iter.next(), None);
+    let expected: Vec<Point> = (0..256).map(|d| d2xy(16, d)).collect();
+    assert_eq!(points, expected);
+
+    let mut backwards: Vec<Point> = RegularHilbertIterator::new_with_iteration(4).rev().collect();
+    backwards.reverse();
+    assert_eq!(backwards, expected);
+}
+
+
+/// Iterates over the lines of the curve, each given by the cells at either end.
 pub struct HilbertIterator {
     iteration: usize,
-    d: usize
+    front: Cursor,
+    back: Cursor,
+    /// The number of lines that are left.
+    remaining: usize
 }
 
 impl HilbertIterator {
@@ -96,9 +291,12 @@ impl HilbertIterator {
 
     #[inline]
     pub fn new_with_iteration(iteration: usize) -> Self {
+        let d_max = (4_usize.pow((iteration) as u32) ) - 1;
         HilbertIterator {
             iteration,
-            d: 0
+            front: Cursor::new(iteration, 0),
+            back: Cursor::new(iteration, d_max),
+            remaining: d_max
         }
     }
 
@@ -114,31 +312,58 @@ impl HilbertIterator {
 impl Iterator for HilbertIterator {
     type Item = (Point, Point);
     fn next(&mut self) -> Option<Self::Item> {
-        let n = self.n();
+        if self.remaining == 0 {
+            return None
+        }
+        self.remaining -= 1;
 
-        let pt_0 = d2xy(n, self.d);
-        self.d += 1;
-        let pt_1 = d2xy(n, self.d);
+        let pt_0 = self.front.point();
+        self.front.step();
+        let pt_1 = self.front.point();
+
+        Some((pt_0, pt_1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
 
-        if self.d > self.d_max() {
-            None
-        } else {
-            Some((pt_0, pt_1))
+impl DoubleEndedIterator for HilbertIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None
         }
+        self.remaining -= 1;
+
+        let pt_1 = self.back.point();
+        self.back.step_back();
+        let pt_0 = self.back.point();
+
+        Some((pt_0, pt_1))
     }
 }
 
+impl ExactSizeIterator for HilbertIterator {}
+
+/// Iterates over the cells of the curve in order.
 pub struct RegularHilbertIterator {
     iteration: usize,
-    d: usize
+    front: Cursor,
+    back: Cursor,
+    /// The number of points that are left.
+    remaining: usize
 }
 
 impl RegularHilbertIterator {
     #[inline]
     pub fn new_with_iteration(iteration: usize) -> Self {
+        let d_max = (4_usize.pow((iteration) as u32) ) - 1;
         RegularHilbertIterator {
             iteration,
-            d: 0
+            front: Cursor::new(iteration, 0),
+            back: Cursor::new(iteration, d_max),
+            remaining: d_max + 1
         }
     }
     pub fn d_max(&self) -> usize {
@@ -154,9 +379,36 @@ impl Iterator for RegularHilbertIterator {
     type Item = Point;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let n = self.n();
-        let ret = Some(d2xy(n, self.d));
-        self.d += 1;
-        ret
+        if self.remaining == 0 {
+            return None
+        }
+        self.remaining -= 1;
+
+        let ret = self.front.point();
+        if self.remaining > 0 {
+            self.front.step();
+        }
+        Some(ret)
     }
-}
\ No newline at end of file
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl DoubleEndedIterator for RegularHilbertIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None
+        }
+        self.remaining -= 1;
+
+        let ret = self.back.point();
+        if self.remaining > 0 {
+            self.back.step_back();
+        }
+        Some(ret)
+    }
+}
+
+impl ExactSizeIterator for RegularHilbertIterator {}
@@ -0,0 +1,48 @@
+use common::l_system::dynamic::DynamicGrammar;
+
+/// The Hilbert curve in three dimensions, from The Algorithmic Beauty of Plants, figure 1.31.
+///
+/// Each iteration fills a cube of 8^n cells, with `F` stepping from one cell to the next.
+pub const SPEC: &str = "
+    axiom = A
+    angle = 90
+    A -> B-F+CFC+F-D&F^D-F+&&CFC+F+B//
+    B -> A&F^CFB^F^D^^-F-D^|F^B|FC^F^A//
+    C -> |D^|F^B-F+C^F^A&&FA&F^C+F+B^F^D//
+    D -> |CFB-F+B|FA&F^A&&FB-F+B|FC//
+";
+
+pub fn grammar() -> DynamicGrammar {
+    DynamicGrammar::parse(SPEC).expect("The spec is valid.")
+}
+
+
+#[test]
+fn fills_the_cube() {
+    use nannou::geom::Point3;
+    use std::collections::HashSet;
+    (1..4).for_each(|n| {
+        let lsystem = grammar().into_lsystem().iterate_n(n);
+        let points: Vec<Point3> = lsystem
+            .reify_3d_iter(std::f32::consts::FRAC_PI_2, 1.0, Point3 { x: 0.0, y: 0.0, z: 0.0 })
+            .collect();
+        let side = 1 << n;
+        assert_eq!(points.len(), side * side * side);
+
+        let cells: HashSet<(i32, i32, i32)> = points
+            .iter()
+            .map(|pt| (pt.x.round() as i32, pt.y.round() as i32, pt.z.round() as i32))
+            .collect();
+        assert_eq!(cells.len(), points.len());
+        points.windows(2).for_each(|pair| {
+            let step = pair[1] - pair[0];
+            assert!((step.dot(step) - 1.0).abs() < 0.001);
+        });
+        let extent = |f: fn(&(i32, i32, i32)) -> i32| {
+            cells.iter().map(f).max().unwrap() - cells.iter().map(f).min().unwrap()
+        };
+        assert_eq!(extent(|c| c.0), side as i32 - 1);
+        assert_eq!(extent(|c| c.1), side as i32 - 1);
+        assert_eq!(extent(|c| c.2), side as i32 - 1);
+    });
+}
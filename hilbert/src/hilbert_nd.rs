@@ -0,0 +1,237 @@
+//! Hilbert curves in any number of dimensions, using John Skilling's algorithm from
+//! "Programming the Hilbert curve" (AIP Conference Proceedings 707, 2004).
+//!
+//! A point is given by its coordinates, each with a number of bits, so the curve fills a cube with a side of 2^bits.
+//! Skilling's algorithm works on the "transpose" of the index:
+//! the bits of the index are dealt out to the coordinates in turn, starting with the most significant,
+//! so the transpose is another set of coordinates, and the algorithm turns one set into the other in place.
+//!
+//! In two dimensions, this is the same curve that `d2xy` gives.
+
+/// The most bits an index can have.
+const INDEX_BITS: usize = 64;
+
+/// Finds the point that is the given distance along the curve.
+///
+/// The curve fills a cube with the given number of dimensions, and a side of 2^bits.
+pub fn index_to_coords(d: u64, dimensions: usize, bits: usize) -> Vec<u64> {
+    assert!(dimensions * bits <= INDEX_BITS, "A {} dimensional curve with {} bits doesn't fit in an index.", dimensions, bits);
+    let mut x = transpose(d, dimensions, bits);
+    if bits == 0 || dimensions == 0 {
+        return x
+    }
+
+    // Gray decode.
+    let t = x[dimensions - 1] >> 1;
+    (1..dimensions).rev().for_each(|i| x[i] ^= x[i - 1]);
+    x[0] ^= t;
+
+    // Undo the excess work, from the smallest subcubes up.
+    (1..bits).map(|k| 1u64 << k).for_each(|q| {
+        let p = q - 1;
+        (0..dimensions).rev().for_each(|i| {
+            if x[i] & q != 0 {
+                // Invert
+                x[0] ^= p;
+            } else {
+                // Exchange
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        });
+    });
+    x
+}
+
+/// Finds how far along the curve a point is, undoing `index_to_coords`.
+///
+/// Every coordinate must be less than 2^bits.
+pub fn coords_to_index(coords: &[u64], bits: usize) -> u64 {
+    let dimensions = coords.len();
+    assert!(dimensions * bits <= INDEX_BITS, "A {} dimensional curve with {} bits doesn't fit in an index.", dimensions, bits);
+    let mut x = coords.to_vec();
+    if bits == 0 || dimensions == 0 {
+        return 0
+    }
+
+    // Inverse undo, from the whole cube down.
+    let mut q = 1 << (bits - 1);
+    while q > 1 {
+        let p = q - 1;
+        (0..dimensions).for_each(|i| {
+            if x[i] & q != 0 {
+                // Invert
+                x[0] ^= p;
+            } else {
+                // Exchange
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        });
+        q >>= 1;
+    }
+
+    // Gray encode.
+    (1..dimensions).for_each(|i| x[i] ^= x[i - 1]);
+    let mut t = 0;
+    let mut q = 1 << (bits - 1);
+    while q > 1 {
+        if x[dimensions - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    x.iter_mut().for_each(|x| *x ^= t);
+
+    untranspose(&x, bits)
+}
+
+/// Deals the bits of the index out to the coordinates, starting with the most significant bit of the first coordinate.
+fn transpose(d: u64, dimensions: usize, bits: usize) -> Vec<u64> {
+    let mut x = vec![0; dimensions];
+    (0..dimensions * bits).for_each(|i| {
+        let bit = (d >> (dimensions * bits - 1 - i)) & 1;
+        let coordinate = i % dimensions;
+        x[coordinate] |= bit << (bits - 1 - i / dimensions);
+    });
+    x
+}
+
+/// Gathers the bits of the coordinates back into an index.
+fn untranspose(x: &[u64], bits: usize) -> u64 {
+    let dimensions = x.len();
+    (0..dimensions * bits).fold(0, |d, i| {
+        let coordinate = i % dimensions;
+        let bit = (x[coordinate] >> (bits - 1 - i / dimensions)) & 1;
+        (d << 1) | bit
+    })
+}
+
+/// Iterates over the cells of the curve through a cube in three dimensions, in order.
+///
+/// As the cells are visited in the order of their index, this can also sort 3D data so that
+/// points close together in space mostly end up close together in the ordering.
+pub struct HilbertIterator3d {
+    bits: usize,
+    d: u64,
+    /// One past the index of the last cell that's left.
+    d_end: u64
+}
+
+impl HilbertIterator3d {
+    /// The curve through a cube with a side of 2^iteration.
+    pub fn new_with_iteration(iteration: usize) -> Self {
+        assert!(3 * iteration < INDEX_BITS, "Iteration {} has too many cells to count.", iteration);
+        HilbertIterator3d {
+            bits: iteration,
+            d: 0,
+            d_end: 1 << (3 * iteration)
+        }
+    }
+
+    pub fn d_max(&self) -> u64 {
+        (1 << (3 * self.bits)) - 1
+    }
+
+    /// The length of the side of the cube.
+    pub fn n(&self) -> u64 {
+        1 << self.bits
+    }
+
+    fn point(&self, d: u64) -> [u64; 3] {
+        let coords = index_to_coords(d, 3, self.bits);
+        [coords[0], coords[1], coords[2]]
+    }
+}
+
+impl Iterator for HilbertIterator3d {
+    type Item = [u64; 3];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.d >= self.d_end {
+            return None
+        }
+        let ret = self.point(self.d);
+        self.d += 1;
+        Some(ret)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.d_end - self.d) as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for HilbertIterator3d {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.d >= self.d_end {
+            return None
+        }
+        self.d_end -= 1;
+        Some(self.point(self.d_end))
+    }
+}
+
+impl ExactSizeIterator for HilbertIterator3d {}
+
+
+#[test]
+fn round_trip() {
+    [(1, 8), (2, 5), (3, 4), (4, 3), (5, 2)].iter().for_each(|&(dimensions, bits)| {
+        (0..1u64 << (dimensions * bits)).for_each(|d| {
+            let coords = index_to_coords(d, dimensions, bits);
+            assert!(coords.iter().all(|x| *x < 1 << bits));
+            assert_eq!(coords_to_index(&coords, bits), d, "{:?}", coords);
+        });
+    });
+
+    // Every bit of the index is used, so the whole cube can't be walked, but some of it can.
+    [(1, 64), (2, 32), (4, 16)].iter().for_each(|&(dimensions, bits)| {
+        [0, 5, 1 << 40, !0 - 1, !0].iter().for_each(|&d| {
+            let coords = index_to_coords(d, dimensions, bits);
+            assert_eq!(coords_to_index(&coords, bits), d, "{:?}", coords);
+        });
+    });
+}
+
+#[test]
+fn steps_between_neighbours() {
+    [(2, 5), (3, 4), (4, 3)].iter().for_each(|&(dimensions, bits)| {
+        assert_eq!(index_to_coords(0, dimensions, bits), vec![0; dimensions]);
+        (1..1u64 << (dimensions * bits)).for_each(|d| {
+            let distance: i64 = index_to_coords(d - 1, dimensions, bits)
+                .iter()
+                .zip(index_to_coords(d, dimensions, bits).iter())
+                .map(|(a, b)| (*a as i64 - *b as i64).abs())
+                .sum();
+            assert_eq!(distance, 1, "between {} and {}", d - 1, d);
+        });
+    });
+}
+
+#[test]
+fn fills_the_cube() {
+    use std::collections::HashSet;
+    let iter = HilbertIterator3d::new_with_iteration(3);
+    assert_eq!(iter.len(), 512);
+    assert_eq!(iter.d_max(), 511);
+    let cells: HashSet<[u64; 3]> = iter.collect();
+    assert_eq!(cells.len(), 512);
+    assert!(cells.iter().all(|cell| cell.iter().all(|x| *x < 8)));
+
+    let forwards: Vec<[u64; 3]> = HilbertIterator3d::new_with_iteration(2).collect();
+    let mut backwards: Vec<[u64; 3]> = HilbertIterator3d::new_with_iteration(2).rev().collect();
+    backwards.reverse();
+    assert_eq!(forwards, backwards);
+}
+
+#[test]
+fn agrees_with_d2xy() {
+    let n = 1 << 5;
+    (0..n * n).for_each(|d| {
+        let pt = crate::hilbert::d2xy(n, d);
+        assert_eq!(index_to_coords(d as u64, 2, 5), vec![pt.x() as u64, pt.y() as u64]);
+    });
+}
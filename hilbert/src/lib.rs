@@ -1,6 +1,11 @@
 mod hilbert;
+pub mod hilbert_3d;
+pub mod gilbert;
+pub mod hilbert_nd;
+pub mod space_filling;
 pub mod day_6;
 pub mod day_7;
 pub mod day_8;
 pub mod day_17;
+pub mod day_20;
 pub use hilbert::*;
@@ -1,9 +1,9 @@
 //use hilbert::day_6;
 //use hilbert::day_7;
-use hilbert::day_17;
+use hilbert::day_20;
 
 fn main() {
-    nannou::app(day_17::Model::init)
-        .update(day_17::Model::update)
+    nannou::app(day_20::Model::init)
+        .update(day_20::Model::update)
         .run();
 }
\ No newline at end of file
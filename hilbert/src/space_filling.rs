@@ -0,0 +1,394 @@
+//! Curves that visit every cell of a square grid, found directly from how far along the curve a cell is.
+//!
+//! Most of them fill a grid with a side of 2^iteration, so they can be swapped for one another,
+//! and they differ in how well they keep cells that are close together on the grid close together along the curve.
+
+use crate::hilbert::{d2xy, xy2d, Point};
+
+/// The number of bits in an index.
+const INDEX_BITS: usize = std::mem::size_of::<usize>() * 8;
+
+/// A curve through every cell of a grid.
+pub trait SpaceFillingCurve {
+    /// The name of the curve, for showing to people.
+    fn name(&self) -> &'static str;
+
+    /// The number of cells along each side of the grid, or along its longer side if it isn't square.
+    fn side_len(&self) -> usize;
+
+    /// The number of cells along the x axis.
+    fn width(&self) -> usize {
+        self.side_len()
+    }
+
+    /// The number of cells along the y axis.
+    fn height(&self) -> usize {
+        self.side_len()
+    }
+
+    /// Finds the cell that is the given distance along the curve.
+    fn index_to_point(&self, d: usize) -> Point;
+
+    /// Finds how far along the curve a cell is, undoing `index_to_point`.
+    fn point_to_index(&self, pt: Point) -> usize;
+
+    /// Whether the last cell of the curve is next to the first, so that it forms a loop.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Iterates over the cells in the order that the curve visits them.
+    fn iter(&self) -> CurveIterator<'_> where Self: Sized {
+        CurveIterator::new(self)
+    }
+}
+
+/// Iterates over the cells of a curve in order.
+pub struct CurveIterator<'a> {
+    curve: &'a dyn SpaceFillingCurve,
+    d: usize,
+    /// One past the index of the last cell that's left.
+    d_end: usize
+}
+
+impl <'a> CurveIterator<'a> {
+    pub fn new(curve: &'a dyn SpaceFillingCurve) -> Self {
+        CurveIterator {
+            curve,
+            d: 0,
+            d_end: curve.width() * curve.height()
+        }
+    }
+}
+
+impl <'a> Iterator for CurveIterator<'a> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.d >= self.d_end {
+            return None
+        }
+        let ret = self.curve.index_to_point(self.d);
+        self.d += 1;
+        Some(ret)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.d_end - self.d;
+        (len, Some(len))
+    }
+}
+
+impl <'a> DoubleEndedIterator for CurveIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.d >= self.d_end {
+            return None
+        }
+        self.d_end -= 1;
+        Some(self.curve.index_to_point(self.d_end))
+    }
+}
+
+impl <'a> ExactSizeIterator for CurveIterator<'a> {}
+
+/// Every curve, for the given iteration, in the order they're cycled through by the sketches.
+///
+/// The Moore curve is left out of iteration 0, as a single cell can't form a loop.
+pub fn curves(iteration: usize) -> Vec<Box<dyn SpaceFillingCurve>> {
+    let mut curves: Vec<Box<dyn SpaceFillingCurve>> = vec![Box::new(Hilbert { iteration })];
+    if let Some(moore) = Moore::new(iteration) {
+        curves.push(Box::new(moore));
+    }
+    curves.push(Box::new(Morton { iteration }));
+    curves.push(Box::new(GrayCode { iteration }));
+    curves.push(Box::new(Snake { iteration }));
+    curves
+}
+
+
+/// The Hilbert curve, which is the same as `d2xy`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hilbert {
+    pub iteration: usize
+}
+
+impl SpaceFillingCurve for Hilbert {
+    fn name(&self) -> &'static str {
+        "Hilbert"
+    }
+
+    fn side_len(&self) -> usize {
+        1 << self.iteration
+    }
+
+    fn index_to_point(&self, d: usize) -> Point {
+        d2xy(self.side_len(), d)
+    }
+
+    fn point_to_index(&self, pt: Point) -> usize {
+        xy2d(self.side_len(), pt)
+    }
+}
+
+
+/// The Moore curve, which joins four Hilbert curves into a loop.
+///
+/// It starts at the bottom of the grid just left of the middle, goes up through the left half,
+/// comes down through the right half, and ends just right of where it started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Moore {
+    iteration: usize
+}
+
+impl Moore {
+    /// The Moore curve through a grid with a side of 2^iteration,
+    /// or `None` for iteration 0, as a loop needs more than one cell.
+    pub fn new(iteration: usize) -> Option<Self> {
+        if iteration == 0 {
+            None
+        } else {
+            Some(Moore { iteration })
+        }
+    }
+
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// The side of each of the Hilbert curves.
+    fn half(&self) -> usize {
+        1 << (self.iteration - 1)
+    }
+}
+
+impl SpaceFillingCurve for Moore {
+    fn name(&self) -> &'static str {
+        "Moore"
+    }
+
+    fn side_len(&self) -> usize {
+        1 << self.iteration
+    }
+
+    fn index_to_point(&self, d: usize) -> Point {
+        let h = self.half();
+        let quadrant = d / (h * h);
+        let pt = d2xy(h, d % (h * h));
+        // The Hilbert curves start and end on their bottom edge,
+        // so those in the left half are turned to start and end on their right edge,
+        // and those in the right half to start and end on their left edge.
+        match quadrant {
+            0 => Point::new(h - 1 - pt.y(), pt.x()),
+            1 => Point::new(h - 1 - pt.y(), h + pt.x()),
+            2 => Point::new(h + pt.y(), 2 * h - 1 - pt.x()),
+            _ => Point::new(h + pt.y(), h - 1 - pt.x()),
+        }
+    }
+
+    fn point_to_index(&self, pt: Point) -> usize {
+        let h = self.half();
+        let (quadrant, local) = match (pt.x() < h, pt.y() < h) {
+            (true, true) => (0, Point::new(pt.y(), h - 1 - pt.x())),
+            (true, false) => (1, Point::new(pt.y() - h, h - 1 - pt.x())),
+            (false, false) => (2, Point::new(2 * h - 1 - pt.y(), pt.x() - h)),
+            (false, true) => (3, Point::new(h - 1 - pt.y(), pt.x() - h)),
+        };
+        quadrant * h * h + xy2d(h, local)
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+}
+
+
+/// The Z-order curve, which interleaves the bits of x and y.
+///
+/// It keeps nearby cells together within each quadrant, but jumps between them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Morton {
+    pub iteration: usize
+}
+
+/// Spreads the bits of a coordinate out so that there's a gap between each of them.
+fn spread(x: usize) -> usize {
+    (0..INDEX_BITS / 2).fold(0, |d, bit| d | (((x >> bit) & 1) << (2 * bit)))
+}
+
+/// Gathers every other bit back together, undoing `spread`.
+fn gather(d: usize) -> usize {
+    (0..INDEX_BITS / 2).fold(0, |x, bit| x | (((d >> (2 * bit)) & 1) << bit))
+}
+
+impl SpaceFillingCurve for Morton {
+    fn name(&self) -> &'static str {
+        "Morton"
+    }
+
+    fn side_len(&self) -> usize {
+        1 << self.iteration
+    }
+
+    fn index_to_point(&self, d: usize) -> Point {
+        Point::new(gather(d), gather(d >> 1))
+    }
+
+    fn point_to_index(&self, pt: Point) -> usize {
+        spread(pt.x()) | (spread(pt.y()) << 1)
+    }
+}
+
+
+/// Visits the cells in the order of the Gray code of their Morton index.
+///
+/// Consecutive Gray codes differ by a single bit,
+/// so each step changes just one bit of either x or y, although that can still be a long way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GrayCode {
+    pub iteration: usize
+}
+
+impl SpaceFillingCurve for GrayCode {
+    fn name(&self) -> &'static str {
+        "Gray code"
+    }
+
+    fn side_len(&self) -> usize {
+        1 << self.iteration
+    }
+
+    fn index_to_point(&self, d: usize) -> Point {
+        Morton { iteration: self.iteration }.index_to_point(d ^ (d >> 1))
+    }
+
+    fn point_to_index(&self, pt: Point) -> usize {
+        let gray = Morton { iteration: self.iteration }.point_to_index(pt);
+        // Each bit of the index is the parity of the bits of the Gray code above it.
+        let mut d = gray;
+        let mut shift = 1;
+        while shift < INDEX_BITS {
+            d ^= d >> shift;
+            shift *= 2;
+        }
+        d
+    }
+}
+
+
+/// Goes back and forth across the rows, like an ox ploughing a field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snake {
+    pub iteration: usize
+}
+
+impl SpaceFillingCurve for Snake {
+    fn name(&self) -> &'static str {
+        "Snake"
+    }
+
+    fn side_len(&self) -> usize {
+        1 << self.iteration
+    }
+
+    fn index_to_point(&self, d: usize) -> Point {
+        let n = self.side_len();
+        let y = d / n;
+        let x = if y & 1 == 0 { d % n } else { n - 1 - d % n };
+        Point::new(x, y)
+    }
+
+    fn point_to_index(&self, pt: Point) -> usize {
+        let n = self.side_len();
+        let x = if pt.y() & 1 == 0 { pt.x() } else { n - 1 - pt.x() };
+        pt.y() * n + x
+    }
+}
+
+
+#[cfg(test)]
+fn distance(a: Point, b: Point) -> usize {
+    let dx = if a.x() > b.x() { a.x() - b.x() } else { b.x() - a.x() };
+    let dy = if a.y() > b.y() { a.y() - b.y() } else { b.y() - a.y() };
+    dx + dy
+}
+
+#[test]
+fn round_trip() {
+    (0..=6).for_each(|iteration| {
+        curves(iteration).iter().for_each(|curve| {
+            let n = curve.side_len();
+            let mut seen = vec![false; n * n];
+            CurveIterator::new(curve.as_ref()).enumerate().for_each(|(d, pt)| {
+                assert!(pt.x() < n && pt.y() < n, "{} left the grid at {}", curve.name(), d);
+                assert_eq!(curve.point_to_index(pt), d, "{}", curve.name());
+                seen[pt.y() * n + pt.x()] = true;
+            });
+            assert!(seen.iter().all(|seen| *seen), "{} missed a cell", curve.name());
+        });
+    });
+}
+
+#[test]
+fn a_single_cell_has_no_moore_curve() {
+    assert_eq!(Moore::new(0), None);
+    assert!(curves(0).iter().all(|curve| curve.name() != "Moore"));
+    assert_eq!(curves(1).len(), 5);
+}
+
+#[test]
+fn continuous_curves_step_between_neighbours() {
+    let iteration = 5;
+    curves(iteration)
+        .iter()
+        .filter(|curve| ["Hilbert", "Moore", "Snake"].contains(&curve.name()))
+        .for_each(|curve| {
+            let points: Vec<Point> = CurveIterator::new(curve.as_ref()).collect();
+            points.windows(2).for_each(|pair| {
+                assert_eq!(distance(pair[0], pair[1]), 1, "{}", curve.name());
+            });
+            let closes = distance(points[0], points[points.len() - 1]) == 1;
+            assert_eq!(closes, curve.is_closed(), "{}", curve.name());
+        });
+}
+
+#[test]
+fn locality() {
+    // The average perimeter of the box around every run of n cells along the curve.
+    // A curve that keeps nearby cells together fills squarish boxes, while the snake fills a whole row at a time.
+    let iteration = 6;
+    let perimeters: Vec<(&'static str, f64)> = curves(iteration)
+        .iter()
+        .map(|curve| {
+            let n = curve.side_len();
+            let points: Vec<Point> = CurveIterator::new(curve.as_ref()).collect();
+            let total: usize = points
+                .windows(n)
+                .map(|run| {
+                    let min_x = run.iter().map(Point::x).min().unwrap();
+                    let max_x = run.iter().map(Point::x).max().unwrap();
+                    let min_y = run.iter().map(Point::y).min().unwrap();
+                    let max_y = run.iter().map(Point::y).max().unwrap();
+                    2 * (max_x - min_x + 1) + 2 * (max_y - min_y + 1)
+                })
+                .sum();
+            (curve.name(), total as f64 / (points.len() - n + 1) as f64)
+        })
+        .collect();
+    // The Hilbert and Moore curves never jump, so they do best, and the Z-order curves still do better than the snake.
+    let perimeter = |name: &str| perimeters.iter().find(|(n, _)| *n == name).unwrap().1;
+    ["Hilbert", "Moore"].iter().for_each(|continuous| {
+        ["Morton", "Gray code"].iter().for_each(|jumping| {
+            assert!(perimeter(continuous) < perimeter(jumping), "{:?}", perimeters);
+            assert!(perimeter(jumping) < perimeter("Snake"), "{:?}", perimeters);
+        });
+    });
+}
+
+#[test]
+fn the_trait_has_an_iterator() {
+    let hilbert = Hilbert { iteration: 3 };
+    let points: Vec<Point> = hilbert.iter().collect();
+    let expected: Vec<Point> = crate::hilbert::RegularHilbertIterator::new_with_iteration(3).collect();
+    assert_eq!(points, expected);
+    assert_eq!(Snake { iteration: 2 }.iter().next_back(), Some(Point::new(0, 3)));
+}
@@ -5,6 +5,8 @@
 use nannou::prelude::*;
 use std::num::Wrapping;
 use common::l_system::{LSystem};
+use common::l_system::morph::Morph;
+use common::fit::fit;
 use crate::koch::Koch;
 
 pub struct Model {
@@ -13,6 +15,8 @@ pub struct Model {
     frame_counter: Wrapping<usize>,
     /// Buffer containing all of the lines needed to draw the complete curve for the current iteration.
     point_buffer: Vec<Point2>,
+    /// The change to the current iteration that is being animated, and how many frames it has been running.
+    morph: Option<(Morph, usize)>,
     iteration: usize,
     thickness: f32,
 
@@ -21,26 +25,33 @@ pub struct Model {
 
 const ITERATION: usize = 3;
 const INITIAL_THICKNESS: f32 = 2.0;
+const WINDOW_SIZE: u32 = 512;
+/// How long it takes for a change of iteration to unfold.
+const MORPH_FRAMES: usize = 30;
+const MARGIN: f32 = 40.0;
 
 
-fn build_point_buffer(iterations: usize) -> Vec<Point2> {
+fn axiom() -> Vec<Koch> {
     use Koch::*;
-    let axiom = vec![F, Minus, Minus, F, Minus, Minus, F];
-    let lsystem = LSystem::new(axiom).iterate_n(iterations);
-    let line_length = 300.0 / (3.pow(iterations as u32)) as f32;
-
-
-    let origin = Point2 {
-        x: -(1.5 * 3.pow((iterations - 1) as u32) as f32) * line_length,
-        y: (((3.0.sqrt() / 2.0) * 3.pow((iterations - 1) as u32) as f32) * line_length) as f32,
-    };
+    vec![F, Minus, Minus, F, Minus, Minus, F]
+}
 
+fn build_point_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Point2> {
+    let lsystem = LSystem::new(axiom()).iterate_n(iterations);
 
     let point_buffer: Vec<Point2> = lsystem
-        .reify_iter(std::f32::consts::FRAC_PI_3 , line_length, origin)
+        .reify_iter(std::f32::consts::FRAC_PI_3 , 1.0, Point2::default())
         .collect();
 
-    point_buffer
+    fit(point_buffer, window_dimensions, MARGIN)
+}
+
+/// Unfolds each line of an iteration into its production in the next one.
+fn build_morph(iteration: usize, window_dimensions: Vector2) -> Morph {
+    LSystem::new(axiom())
+        .iterate_n(iteration)
+        .morph(std::f32::consts::FRAC_PI_3, 1.0, Point2::default())
+        .fit(window_dimensions, MARGIN)
 }
 
 
@@ -48,7 +59,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title("day 11")
             .view(view) // The function that will be called for presenting graphics to a frame.
             .event(event) // The function that will be called when the window receives events.
@@ -56,13 +67,15 @@ impl Model {
             .build()
             .unwrap();
 
-        let point_buffer = build_point_buffer(ITERATION);
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(ITERATION, window_dimensions);
 
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             point_buffer,
+            morph: None,
             iteration: ITERATION,
             thickness: INITIAL_THICKNESS,
         }
@@ -70,11 +83,22 @@ impl Model {
 
     pub fn update(_app: &App, model: &mut Model, _update: Update) {
         model.frame_counter += Wrapping(1);
+        if let Some((morph, frame)) = model.morph.as_mut() {
+            *frame += 1;
+            if *frame < MORPH_FRAMES {
+                model.point_buffer = morph.at(*frame as f32 / MORPH_FRAMES as f32);
+            } else {
+                model.morph = None;
+                model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions);
+            }
+        }
     }
 }
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.morph = None;
+    model.point_buffer = build_point_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -88,15 +112,15 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
             match key {
                 Key::Right => {
                     if model.iteration < 7 {
+                        model.morph = Some((build_morph(model.iteration, model.window_dimensions), 0));
                         model.iteration += 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
                 },
                 Key::Left => {
                     if model.iteration > 0 {
                         model.iteration -= 1;
+                        model.morph = Some((build_morph(model.iteration, model.window_dimensions).reversed(), 0));
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
                 }
                 Key::Up => {
                     model.thickness += 1.0;
@@ -148,7 +172,7 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
 
     model.point_buffer
         .windows(2)
-        .zip(nannou::color::Gradient::with_domain(colors.clone())
+        .zip(nannou::color::Gradient::with_domain(colors)
             .take(len)
             .cycle()
             .skip(skip)
@@ -167,22 +191,6 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
                 .radius(half_thickness);
         });
 
-    let last_color = nannou::color::Gradient::with_domain(colors)
-        .take(len)
-        .cycle()
-        .skip(skip)
-        .next().unwrap();
-
-    draw.line()
-        .start(model.point_buffer[0])
-        .end(model.point_buffer[model.point_buffer.len() - 1])
-        .thickness(model.thickness)
-        .color(last_color);
-    draw.ellipse()
-        .xy(model.point_buffer[0])
-        .color(last_color)
-        .radius(half_thickness);
-
     // Write to the window frame.
     draw.to_frame(app, &frame).unwrap();
     // Return the drawn frame.
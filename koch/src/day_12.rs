@@ -4,6 +4,7 @@
 use nannou::prelude::*;
 use std::num::Wrapping;
 use common::l_system::{LSystem};
+use common::fit::fit;
 use crate::koch::Koch;
 use nannou::app::Draw;
 
@@ -19,25 +20,24 @@ pub struct Model {
 
 
 const ITERATION: usize = 3;
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 5.0;
 
 
-fn build_point_buffer(iterations: usize) -> Vec<Point2> {
+/// Builds the snowflake in the middle of the window.
+///
+/// The others are drawn around it at twice the distance of its outer corners,
+/// so it only gets a third of the window.
+fn build_point_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Point2> {
     use Koch::*;
     let axiom = vec![F, Minus, Minus, F, Minus, Minus, F];
     let lsystem = LSystem::new(axiom).iterate_n(iterations);
-    let line_length: f32 = 200.0 / (3.pow(iterations as u32)) as f32;
-
-    let origin = Point2 {
-        x: -(1.5 * 3.pow((iterations - 1) as u32) as f32) * line_length,
-        y: (((3.0.sqrt() / 2.0) * 3.pow((iterations - 1) as u32) as f32) * line_length) as f32,
-    };
-
 
     let point_buffer: Vec<Point2> = lsystem
-        .reify_iter(std::f32::consts::FRAC_PI_3 , line_length, origin)
+        .reify_iter(std::f32::consts::FRAC_PI_3 , 1.0, Point2::default())
         .collect();
 
-    point_buffer
+    fit(point_buffer, window_dimensions / 3.0, MARGIN)
 }
 
 
@@ -45,7 +45,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title("day 12")
             .view(view)
             .event(event)
@@ -53,11 +53,12 @@ impl Model {
             .build()
             .unwrap();
 
-        let point_buffer = build_point_buffer(ITERATION);
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(ITERATION, window_dimensions);
 
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             point_buffer,
             iteration: ITERATION,
@@ -71,6 +72,7 @@ impl Model {
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.point_buffer = build_point_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -86,13 +88,13 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
                     if model.iteration < 7 {
                         model.iteration += 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 },
                 Key::Left => {
                     if model.iteration > 0 {
                         model.iteration -= 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 }
                 Key::Q => {
                     std::process::exit(0); // Q -> exit program
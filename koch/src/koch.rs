@@ -1,10 +1,10 @@
-use common::l_system::Grammar;
+use common::l_system::{Alphabet, Grammar};
+use common::l_system::turtle::Command;
 use nannou::geom::Point2;
-use common::point_ext::line_to;
 
 ///
-/// Angle should be 120 deg
-#[derive(Clone, Copy, Debug)]
+/// Angle should be 60 deg
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Koch {
     F,
     Plus,
@@ -24,19 +24,66 @@ impl Grammar for Koch {
         }
     }
 
-    fn reify(v: &Self, current_pt: &mut Self::Item, current_angle: &mut f32, angle_step: f32, line_length: f32) -> Option<Self::Item>
-    {
+    fn command(&self) -> Option<Command> {
         use Koch::*;
-        let cpy = current_pt.clone();
-        match v {
-            F => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            Plus => *current_angle += angle_step,
-            Minus => *current_angle -= angle_step,
-        };
-        match v {
-            F => Some(cpy),
-            Plus | Minus => None
+        match self {
+            F => Some(Command::Draw),
+            Plus => Some(Command::TurnLeft),
+            Minus => Some(Command::TurnRight)
+        }
+    }
+}
+
+impl Alphabet for Koch {
+    fn alphabet() -> Vec<Self> {
+        use Koch::*;
+        vec![F, Plus, Minus]
+    }
+
+    fn to_char(&self) -> char {
+        use Koch::*;
+        match self {
+            F => 'F',
+            Plus => '+',
+            Minus => '-'
         }
     }
 }
 
+#[test]
+fn fractint_export() {
+    use common::l_system::dynamic::DynamicGrammar;
+    use common::l_system::fractint;
+    use Koch::*;
+    let grammar = DynamicGrammar::from_alphabet(&[F, Minus, Minus, F, Minus, Minus, F], std::f32::consts::FRAC_PI_3);
+    assert_eq!(fractint::write("Koch", &grammar).unwrap(), "Koch {\n  Angle 6\n  Axiom F--F--F\n  F=F+F--F+F\n}\n");
+}
+
+#[test]
+fn snowflake_closes() {
+    use common::l_system::LSystem;
+    use Koch::*;
+    let origin = Point2 { x: 0.0, y: 0.0 };
+    let lsystem = LSystem::new(vec![F, Minus, Minus, F, Minus, Minus, F]).iterate_n(4);
+    let points: Vec<Point2> = lsystem.reify_iter(std::f32::consts::FRAC_PI_3, 1.0, origin).collect();
+    assert_eq!(points.len(), 3 * 4usize.pow(4) + 1);
+    let end = points[points.len() - 1];
+    assert!(end.x.abs() < 0.01 && end.y.abs() < 0.01, "{:?}", end);
+}
+
+#[test]
+fn diagnostics() {
+    use common::l_system::LSystem;
+    use Koch::*;
+    let lsystem = LSystem::new(vec![F, Minus, Minus, F, Minus, Minus, F]);
+    let diagnostics = lsystem.diagnose(&[F, Plus, Minus], std::f32::consts::FRAC_PI_3, 3).unwrap();
+    assert!(diagnostics.unreachable.is_empty());
+    assert!(diagnostics.barren.is_empty());
+    assert!((diagnostics.growth_rate - 4.0).abs() < 0.01);
+    assert!(diagnostics.closes);
+    assert_eq!(diagnostics.intersection, None);
+
+    // At 120 degrees, each bump folds back onto the line it came from.
+    let diagnostics = lsystem.diagnose(&[F, Plus, Minus], 2.0 * std::f32::consts::FRAC_PI_3, 3).unwrap();
+    assert!(diagnostics.intersection.is_some());
+}
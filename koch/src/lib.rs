@@ -1,4 +1,4 @@
 
-mod koch;
+pub mod koch;
 pub mod day_11;
 pub mod day_12;
@@ -4,7 +4,9 @@ use nannou::prelude::*;
 use std::num::Wrapping;
 use common::l_system::{LSystem};
 use crate::peano::Peano;
-use common::collinear::{condense_collinear};
+use common::collinear::condense_collinear_by;
+use common::l_system::lattice::LatticePoint;
+use common::fit::fit;
 use common::draw::rainbow_lines;
 
 pub struct Model {
@@ -19,48 +21,26 @@ pub struct Model {
 
 
 const ITERATION: usize = 3;
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 40.0;
 
 
-fn build_point_buffer(iterations: usize) -> Vec<Point2> {
+fn build_point_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Point2> {
     use Peano::*;
     let axiom = vec![L];
     let lsystem = LSystem::new(axiom).iterate_n(iterations);
-    let line_length: f32 = 400.0 / (3.pow(iterations as u32)) as f32;
-
-    // Rule = f(n-1) * 3 + 1
-    fn offset(n: usize) -> usize {
-        if n == 0 {
-            0
-        } else {
-            offset(n - 1) * 3 + 1
-        }
-    }
-
-    let o = offset(iterations) as f32;
-
-    let origin = Point2 {
-        x: -(o * line_length),
-        y: (o * line_length)
-    };
 
+    // Reifying on the lattice lets collinear points be found exactly.
+    let (lattice, points) = lsystem
+        .reify_lattice_iter(std::f32::consts::FRAC_PI_2)
+        .expect("Right angles stay on the lattice.");
 
-
-    let mut point_buffer: Vec<Point2> = lsystem
-        .reify_iter(std::f32::consts::FRAC_PI_2 , line_length, origin)
+    let point_buffer = condense_collinear_by(points.collect(), LatticePoint::are_collinear)
+        .into_iter()
+        .map(|pt| lattice.to_point(pt, 1.0, Point2::default()))
         .collect();
 
-    // for some reason, the last point doesn't get created, so this section generates it for us
-    if let Some(last) = point_buffer.get(point_buffer.len() - 1) {
-        let last = Point2 {
-            x: last.x + line_length,
-            y: last.y
-        };
-        point_buffer.push(last)
-    }
-
-    let point_buffer = condense_collinear(point_buffer);
-
-    point_buffer
+    fit(point_buffer, window_dimensions, MARGIN)
 }
 
 
@@ -71,7 +51,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title("day 13")
             .view(view)
             .event(event)
@@ -79,11 +59,12 @@ impl Model {
             .build()
             .unwrap();
 
-        let point_buffer = build_point_buffer(ITERATION);
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(ITERATION, window_dimensions);
 
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             point_buffer,
             iteration: ITERATION,
@@ -98,6 +79,7 @@ impl Model {
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.point_buffer = build_point_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -113,13 +95,13 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
                     if model.iteration < 7 {
                         model.iteration += 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 },
                 Key::Left => {
                     if model.iteration > 0 {
                         model.iteration -= 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 }
                 Key::Up => {
                     model.thickness += 1.0;
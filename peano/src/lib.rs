@@ -1,3 +1,3 @@
 
-mod peano;
+pub mod peano;
 pub mod day_13;
\ No newline at end of file
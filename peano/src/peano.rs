@@ -1,6 +1,6 @@
-use common::l_system::Grammar;
+use common::l_system::{Alphabet, Grammar};
+use common::l_system::turtle::Command;
 use nannou::geom::Point2;
-use common::point_ext::line_to;
 
 ///
 /// Angle should be 90 deg
@@ -28,20 +28,54 @@ impl Grammar for Peano {
         }
     }
 
-    fn reify(v: &Self, current_pt: &mut Self::Item, current_angle: &mut f32, angle_step: f32, line_length: f32) -> Option<Self::Item>
-    {
+    fn command(&self) -> Option<Command> {
         use Peano::*;
-        let cpy = current_pt.clone();
-        match v {
-            F => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            L | R => {},
-            Plus => *current_angle += angle_step,
-            Minus => *current_angle -= angle_step,
-        };
-        match v {
-            F => Some(cpy),
-            Plus | Minus | L | R => None
+        match self {
+            F => Some(Command::Draw),
+            Plus => Some(Command::TurnLeft),
+            Minus => Some(Command::TurnRight),
+            L | R => None
         }
     }
 }
 
+impl Alphabet for Peano {
+    fn alphabet() -> Vec<Self> {
+        use Peano::*;
+        vec![F, L, R, Plus, Minus]
+    }
+
+    fn to_char(&self) -> char {
+        use Peano::*;
+        match self {
+            F => 'F',
+            L => 'L',
+            R => 'R',
+            Plus => '+',
+            Minus => '-'
+        }
+    }
+}
+
+#[test]
+fn fractint_export() {
+    use common::l_system::dynamic::DynamicGrammar;
+    use common::l_system::fractint;
+    use Peano::*;
+    let grammar = DynamicGrammar::from_alphabet(&[L], std::f32::consts::FRAC_PI_2);
+    assert_eq!(fractint::write("Peano", &grammar).unwrap(), "Peano {\n  Angle 4\n  Axiom L\n  L=LFRFL-F-RFLFR+F+LFRFL\n  R=RFLFR+F+LFRFL-F-RFLFR\n}\n");
+}
+
+#[test]
+fn ends_in_the_opposite_corner() {
+    use common::l_system::LSystem;
+    (1..5).for_each(|n| {
+        let end = LSystem::new(vec![Peano::L])
+            .iterate_n(n)
+            .reify_iter(std::f32::consts::FRAC_PI_2, 1.0, Point2 { x: 0.0, y: 0.0 })
+            .last()
+            .unwrap();
+        let side = (3i32.pow(n as u32) - 1) as f32;
+        assert!((end.x - side).abs() < 0.01 && (end.y + side).abs() < 0.01, "{:?}", end);
+    });
+}
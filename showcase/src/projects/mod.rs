@@ -1,7 +1,7 @@
 //! # IDEAS
 //!
 //! * Color space transitions.                                  -- day 7
-//! * Trees
+//! * Trees                                                     -- day 18
 //! * Maze generation
 //! * Learn how the fuck to use shaders.
 //! * Space filling curves !!!                                  -- day 6, 7, 8
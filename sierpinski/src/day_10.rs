@@ -5,6 +5,7 @@
 use nannou::prelude::*;
 use std::num::Wrapping;
 use common::l_system::{LSystem};
+use common::fit::fit;
 use crate::sierpinski::Sierpinski;
 
 pub struct Model {
@@ -20,30 +21,22 @@ pub struct Model {
 
 const ITERATION: usize = 3;
 const INITIAL_THICKNESS: f32 = 2.0;
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 20.0;
 
 
-/// Uses an L-system + Gosper grammar to construct a list of items.
-/// Since the l system is reified with points starting at the center,
-/// the path is not centered on the screen.
-///
-/// So an approximate spacial center of the path is found, and the points are all then offset from that.
-fn build_point_buffer(iterations: usize) -> Vec<Point2> {
+/// Uses an L-system + Sierpinski grammar to construct a list of items,
+/// which are then centered in the window and scaled to fill it.
+fn build_point_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Point2> {
     use Sierpinski::*;
     let axiom = vec![F, Minus, G, Minus, G];
     let lsystem = LSystem::new(axiom).iterate_n(iterations);
 
-    let line_length = 550.0 / 2.pow(iterations as u32) as f32;
-
-    let origin = Point2 {
-        x: -(2.pow(iterations as u32) as f32 / 2.0) * line_length,
-        y: -(2.pow(iterations as u32) as f32 * 3.0.sqrt() / 2.0) * line_length / 2.0
-    };
-
     let point_buffer: Vec<Point2> = lsystem
-        .reify_iter(std::f32::consts::FRAC_PI_3 * 2.0, line_length, origin)
+        .reify_iter(std::f32::consts::FRAC_PI_3 * 2.0, 1.0, Point2::default())
         .collect();
 
-    point_buffer
+    fit(point_buffer, window_dimensions, MARGIN)
 }
 
 
@@ -51,7 +44,7 @@ impl Model {
     pub fn init(app: &App) -> Model {
         let _window = app
             .new_window()
-            .with_dimensions(512, 512)
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
             .with_title("day 11")
             .view(view) // The function that will be called for presenting graphics to a frame.
             .event(event) // The function that will be called when the window receives events.
@@ -59,11 +52,12 @@ impl Model {
             .build()
             .unwrap();
 
-        let point_buffer = build_point_buffer(ITERATION);
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let point_buffer = build_point_buffer(ITERATION, window_dimensions);
 
         Model {
             _window,
-            window_dimensions: Vector2::default(),
+            window_dimensions,
             frame_counter: Wrapping(0),
             point_buffer,
             iteration: ITERATION,
@@ -78,6 +72,7 @@ impl Model {
 
 fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
     model.window_dimensions = dimensions;
+    model.point_buffer = build_point_buffer(model.iteration, dimensions);
 }
 
 /// Handle events related to the window and update the model if necessary
@@ -93,13 +88,13 @@ fn event(_app: &App, model: &mut Model, event: WindowEvent) {
                     if model.iteration < 10 {
                         model.iteration += 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 },
                 Key::Left => {
                     if model.iteration > 0 {
                         model.iteration -= 1;
                     }
-                    model.point_buffer = build_point_buffer(model.iteration)
+                    model.point_buffer = build_point_buffer(model.iteration, model.window_dimensions)
                 }
                 Key::Up => {
                     model.thickness += 1.0;
@@ -147,7 +142,7 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
 
     model.point_buffer
         .windows(2)
-        .zip(nannou::color::Gradient::with_domain(colors.clone())
+        .zip(nannou::color::Gradient::with_domain(colors)
             .take(len)
             .cycle()
             .skip(skip)
@@ -166,22 +161,6 @@ fn view(app: &App, model: &Model, frame: Frame) -> Frame {
                 .radius(half_thickness);
         });
 
-    let last_color = nannou::color::Gradient::with_domain(colors)
-        .take(len)
-        .cycle()
-        .skip(skip)
-        .next().unwrap();
-
-    draw.line()
-        .start(model.point_buffer[0])
-        .end(model.point_buffer[model.point_buffer.len() - 1])
-        .thickness(model.thickness)
-        .color(last_color);
-    draw.ellipse()
-        .xy(model.point_buffer[0])
-        .color(last_color)
-        .radius(half_thickness);
-
     // Write to the window frame.
     draw.to_frame(app, &frame).unwrap();
     // Return the drawn frame.
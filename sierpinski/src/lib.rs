@@ -1,3 +1,3 @@
 
-mod sierpinski;
+pub mod sierpinski;
 pub mod day_10;
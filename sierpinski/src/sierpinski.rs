@@ -1,9 +1,10 @@
-use common::l_system::Grammar;
+use common::l_system::{Alphabet, Grammar};
+use common::l_system::turtle::Command;
 use nannou::geom::Point2;
 
 ///
 /// Angle should be 120 deg
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Sierpinski {
     F,
     G,
@@ -25,30 +26,77 @@ impl Grammar for Sierpinski {
         }
     }
 
-    fn reify(v: &Self, current_pt: &mut Self::Item, current_angle: &mut f32, angle_step: f32, line_length: f32) -> Option<Self::Item>
-    {
+    fn command(&self) -> Option<Command> {
         use Sierpinski::*;
-        let cpy = current_pt.clone();
-        match v {
-            F => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            G => *current_pt = line_to(*current_pt, *current_angle, line_length),
-            Plus => *current_angle -= angle_step,
-            Minus => *current_angle += angle_step,
-        };
-
-        match v {
-            F | G => Some(cpy),
-            Plus | Minus => None
+        match self {
+            F | G => Some(Command::Draw),
+            Plus => Some(Command::TurnLeft),
+            Minus => Some(Command::TurnRight)
         }
     }
 }
 
 
-/// Produces a new point from the current one
-#[inline]
-fn line_to(pt: Point2, angle: f32, line_length: f32) -> Point2 {
-    Point2 {
-        x: pt.x + line_length * angle.cos(),
-        y: pt.y + line_length * angle.sin()
+impl Alphabet for Sierpinski {
+    fn alphabet() -> Vec<Self> {
+        use Sierpinski::*;
+        vec![F, G, Plus, Minus]
+    }
+
+    fn to_char(&self) -> char {
+        use Sierpinski::*;
+        match self {
+            // Fractint only draws with F and D.
+            F => 'F',
+            G => 'D',
+            Plus => '+',
+            Minus => '-'
+        }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn fractint_export() {
+    use common::l_system::dynamic::DynamicGrammar;
+    use common::l_system::fractint;
+    use Sierpinski::*;
+    use common::l_system::LSystem;
+    let axiom = vec![F, Minus, G, Minus, G];
+    let angle = std::f32::consts::FRAC_PI_3 * 2.0;
+    let grammar = DynamicGrammar::from_alphabet(&axiom, angle);
+    let text = fractint::write("Sierpinski", &grammar).unwrap();
+    assert_eq!(text, "Sierpinski {\n  Angle 3\n  Axiom F-D-D\n  D=DD\n  F=F-D+F+D-F\n}\n");
+
+    // The curve should be drawn the same once read back in.
+    let (_, imported) = fractint::parse(&text).unwrap().remove(0);
+    let expected: Vec<Point2> = LSystem::new(axiom).iterate_n(3).reify_iter(angle, 1.0, Point2::default()).collect();
+    let actual: Vec<Point2> = imported.into_lsystem().iterate_n(3).reify_iter(angle, 1.0, Point2::default()).collect();
+    assert_eq!(expected.len(), actual.len());
+    expected.iter().zip(actual.iter()).for_each(|(e, a)| {
+        assert!((e.x - a.x).abs() < 0.001 && (e.y - a.y).abs() < 0.001);
+    });
+}
+
+#[test]
+fn triangle_closes() {
+    use common::l_system::LSystem;
+    use Sierpinski::*;
+    let origin = Point2 { x: 0.0, y: 0.0 };
+    let lsystem = LSystem::new(vec![F, Minus, G, Minus, G]).iterate_n(5);
+    let points: Vec<Point2> = lsystem.reify_iter(2.0 * std::f32::consts::FRAC_PI_3, 1.0, origin).collect();
+    let end = points[points.len() - 1];
+    assert!(end.x.abs() < 0.01 && end.y.abs() < 0.01, "{:?}", end);
+}
+
+#[test]
+fn diagnostics() {
+    use common::l_system::LSystem;
+    use Sierpinski::*;
+    let lsystem = LSystem::new(vec![F, Minus, G, Minus, G]);
+    let diagnostics = lsystem.diagnose(&[F, G, Plus, Minus], 2.0 * std::f32::consts::FRAC_PI_3, 3).unwrap();
+    assert!(diagnostics.unreachable.is_empty());
+    assert!(diagnostics.barren.is_empty());
+    assert!(diagnostics.closes);
+    // The inner triangles share their corners with the outer ones.
+    assert!(diagnostics.intersection.is_some());
+}
@@ -0,0 +1,147 @@
+//! Tapering tree
+
+use nannou::prelude::*;
+use std::num::Wrapping;
+use common::l_system::{LSystem};
+use crate::tree::Tree;
+use common::draw::{colored_lines_no_corners, rainbow};
+use common::fit::BoundingBox;
+
+pub struct Model {
+    _window: WindowId,
+    window_dimensions: Vector2,
+    frame_counter: Wrapping<usize>,
+    /// Buffer containing a polyline for every branch of the tree for the current iteration.
+    branch_buffer: Vec<Vec<Point2>>,
+    iteration: usize,
+    thickness: f32,
+}
+
+
+const ITERATION: usize = 4;
+const ITERATION_LIMIT: usize = 14;
+const TITLE: &str = "day 18";
+const WINDOW_SIZE: u32 = 512;
+const MARGIN: f32 = 20.0;
+const INITIAL_THICKNESS: f32 = 2.0;
+const THICKNESS_STEP: f32 = 0.5;
+
+
+fn build_branch_buffer(iterations: usize, window_dimensions: Vector2) -> Vec<Vec<Point2>> {
+    use Tree::*;
+    // Start by pointing upwards.
+    let axiom = vec![Plus(std::f32::consts::FRAC_PI_2), A(1.0)];
+    let lsystem = LSystem::new(axiom).iterate_n(iterations);
+
+    let branch_buffer: Vec<Vec<Point2>> = lsystem
+        .reify_branches_iter(1.0, 1.0, Point2::default())
+        .collect();
+
+    // Every branch is moved the same way, so the tree is fitted as a whole.
+    match BoundingBox::of(branch_buffer.iter().flatten()) {
+        Some(bounds) => {
+            let fit = bounds.fit(window_dimensions, MARGIN);
+            branch_buffer
+                .into_iter()
+                .map(|branch| branch.into_iter().map(|pt| fit.apply(pt)).collect())
+                .collect()
+        }
+        None => branch_buffer
+    }
+}
+
+
+
+
+
+impl Model {
+    pub fn init(app: &App) -> Model {
+        let _window = app
+            .new_window()
+            .with_dimensions(WINDOW_SIZE, WINDOW_SIZE)
+            .with_title(TITLE)
+            .view(view)
+            .event(event)
+            .resized(on_resize)
+            .build()
+            .unwrap();
+
+        let window_dimensions = vec2(WINDOW_SIZE as f32, WINDOW_SIZE as f32);
+        let branch_buffer = build_branch_buffer(ITERATION, window_dimensions);
+
+        Model {
+            _window,
+            window_dimensions,
+            frame_counter: Wrapping(0),
+            branch_buffer,
+            iteration: ITERATION,
+            thickness: INITIAL_THICKNESS
+        }
+    }
+
+    pub fn update(_app: &App, model: &mut Model, _update: Update) {
+        model.frame_counter += Wrapping(1);
+    }
+}
+
+fn on_resize(_: &App, model: &mut Model, dimensions: Vector2) {
+    model.window_dimensions = dimensions;
+    model.branch_buffer = build_branch_buffer(model.iteration, dimensions);
+}
+
+/// Handle events related to the window and update the model if necessary
+fn event(_app: &App, model: &mut Model, event: WindowEvent) {
+    match event {
+        WindowEvent::MouseMoved(_point) => {
+        }
+        WindowEvent::MousePressed(_) => {
+        }
+        WindowEvent::KeyPressed(key) => {
+            match key {
+                Key::Right => {
+                    if model.iteration < ITERATION_LIMIT {
+                        model.iteration += 1;
+                    }
+                    model.branch_buffer = build_branch_buffer(model.iteration, model.window_dimensions)
+                },
+                Key::Left => {
+                    if model.iteration > 0 {
+                        model.iteration -= 1;
+                    }
+                    model.branch_buffer = build_branch_buffer(model.iteration, model.window_dimensions)
+                }
+                Key::Up => {
+                    model.thickness += THICKNESS_STEP;
+                }
+                Key::Down => {
+                    model.thickness -= THICKNESS_STEP;
+                }
+                Key::Q => {
+                    std::process::exit(0); // Q -> exit program
+                }
+                _ => {}
+            }
+
+        }
+        _ => {}
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) -> Frame {
+    let draw = app.draw();
+
+    frame.clear(DARK_CHARCOAL);
+
+    let skip = model.frame_counter.0;
+
+    model.branch_buffer
+        .iter()
+        .for_each(|branch| {
+            colored_lines_no_corners(&draw, branch, model.thickness, skip % branch.len(), rainbow());
+        });
+
+    // Write to the window frame.
+    draw.to_frame(app, &frame).unwrap();
+    // Return the drawn frame.
+    frame
+}
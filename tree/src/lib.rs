@@ -0,0 +1,2 @@
+mod tree;
+pub mod day_18;
\ No newline at end of file
@@ -0,0 +1,7 @@
+use tree::day_18;
+
+fn main() {
+    nannou::app(day_18::Model::init)
+        .update(day_18::Model::update)
+        .run();
+}
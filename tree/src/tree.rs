@@ -0,0 +1,90 @@
+use common::l_system::Grammar;
+use common::l_system::turtle::{Command, Turtle2};
+use nannou::geom::Point2;
+
+/// How much shorter the left child branch is than its parent.
+const LEFT_RATIO: f32 = 0.75;
+/// How much shorter the right child branch is than its parent.
+const RIGHT_RATIO: f32 = 0.65;
+const LEFT_ANGLE: f32 = std::f32::consts::PI / 6.0;
+const RIGHT_ANGLE: f32 = std::f32::consts::PI / 8.0;
+/// Tips shorter than this stop growing.
+const MIN_LENGTH: f32 = 0.05;
+
+/// A parametric tree whose branches taper as they grow.
+///
+/// Symbols carry their own length or angle,
+/// so the line length and angle passed to `reify_iter` act as scales for these parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tree {
+    /// Draws a branch of the given length.
+    F(f32),
+    /// A growing tip that will become a branch of the given length.
+    A(f32),
+    /// Turns left by the given angle.
+    Plus(f32),
+    /// Turns right by the given angle.
+    Minus(f32),
+    Push,
+    Pop
+}
+
+
+impl Grammar for Tree {
+    type Item = Point2;
+
+    fn production_rules(self) -> Vec<Self> {
+        use Tree::*;
+        match self {
+            A(len) if len >= MIN_LENGTH => vec![
+                F(len),
+                Push, Plus(LEFT_ANGLE), A(len * LEFT_RATIO), Pop,
+                Push, Minus(RIGHT_ANGLE), A(len * RIGHT_RATIO), Pop
+            ],
+            other => vec![other]
+        }
+    }
+
+    fn command(&self) -> Option<Command> {
+        match self {
+            Tree::Push => Some(Command::Push),
+            Tree::Pop => Some(Command::Pop),
+            _ => None
+        }
+    }
+
+    fn reify(v: &Self, turtle: &mut Turtle2<Self::Item>) -> Option<Self::Item>
+    {
+        use Tree::*;
+        match v {
+            F(len) => return Some(turtle.forward(*len)),
+            Plus(angle) => turtle.turn(turtle.angle() * angle),
+            Minus(angle) => turtle.turn(-turtle.angle() * angle),
+            A(_) | Push | Pop => {}
+        };
+        None
+    }
+}
+
+#[test]
+fn branches_taper() {
+    use common::l_system::LSystem;
+    let lsystem = LSystem::new(vec![Tree::A(1.0)]).iterate_n(2);
+    let lengths: Vec<f32> = lsystem
+        .iter()
+        .filter_map(|v| match v {
+            Tree::F(len) => Some(*len),
+            _ => None
+        })
+        .collect();
+    assert_eq!(lengths, vec![1.0, LEFT_RATIO, RIGHT_RATIO]);
+}
+
+#[test]
+fn tips_stop_growing() {
+    use common::l_system::LSystem;
+    // The left branch shrinks the slowest, and falls below the minimum after 11 iterations.
+    let grown = LSystem::new(vec![Tree::A(1.0)]).iterate_n(11);
+    let len = grown.len();
+    assert_eq!(grown.iterate().len(), len);
+}